@@ -100,7 +100,7 @@ impl ArgsContext {
     }
 
     /// Returns the key if the arg represents a kwarg key, otherwise None
-    fn get_kwarg_key(arg: &str) -> Option<String> {
+    pub(crate) fn get_kwarg_key(arg: &str) -> Option<String> {
         lazy_static! {
             static ref KWARG_KEY_REGEX: Regex = Regex::new(r"-{1,2}(?P<key>[a-zA-Z]+\w*)").unwrap();
         }
@@ -114,7 +114,7 @@ impl ArgsContext {
     }
 
     /// Returns the key and value if the arg represents a kwarg key-value pair, otherwise None
-    fn get_kwarg(arg: &str) -> Option<(String, String)> {
+    pub(crate) fn get_kwarg(arg: &str) -> Option<(String, String)> {
         lazy_static! {
             static ref KWARG_REGEX: Regex =
                 Regex::new(r"-{1,2}(?P<key>[a-zA-Z]+\w*)=(?P<val>[\s\S]*)").unwrap();
@@ -2,14 +2,16 @@
 #[path = "builtin_commands_test.rs"]
 mod builtin_commands_test;
 
+use std::io::Write;
+
 use crate::types::DynErrResult;
 
-/// Represents a built-in command.
-type BuiltInCommand = fn(args: &[&str]) -> DynErrResult<()>;
+/// Represents a built-in command, which writes its output to the given writer.
+type BuiltInCommand = fn(args: &[&str], out: &mut dyn Write) -> DynErrResult<()>;
 
 /// Creates an echo built-in command.
-fn echo_command(args: &[&str]) -> DynErrResult<()> {
-    println!("{}", args.join(" "));
+fn echo_command(args: &[&str], out: &mut dyn Write) -> DynErrResult<()> {
+    writeln!(out, "{}", args.join(" "))?;
     Ok(())
 }
 
@@ -3,7 +3,8 @@ use super::*;
 #[test]
 fn test_echo_command() {
     let args = vec!["Hello", "World"];
-    let result = echo_command(&args);
+    let mut out = Vec::new();
+    let result = echo_command(&args, &mut out);
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), ());
+    assert_eq!(String::from_utf8(out).unwrap(), "Hello World\n");
 }
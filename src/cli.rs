@@ -6,16 +6,29 @@ use clap::ArgAction;
 use colored::{ColoredString, Colorize};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::{env, fmt};
 
 use crate::args::ArgsContext;
-use crate::mom_file_paths::{GlobalMomFilePath, MomFilePaths, PathIterator, SingleMomFilePath};
-use crate::mom_files::MomFile;
+use crate::completions::{get_completion_script, SUPPORTED_SHELLS};
+use crate::errors::{AwareTaskError, FileTaskError, TasksFailedError};
+use crate::explain::TaskExplanation;
+use crate::init::{init_mom_file, DEFAULT_MOM_FILE_NAME};
+use crate::mom_file_paths::{
+    get_default_global_mom_file_path, GlobalMomFilePath, MomFilePaths, PathIterator,
+    SingleMomFilePath,
+};
+use crate::mom_files::{MomFile, Problem};
 use crate::mom_files_container::MomFilesContainer;
+use crate::overrides::Overrides;
+use crate::params::Param;
 use crate::print_utils::MomOutput;
+use crate::run_context::RunContext;
+use crate::tasks::{Task, DRY_RUN_MESSAGE};
 use crate::types::DynErrResult;
+use crate::utils::get_suggestions;
+use crate::watch::watch_task;
 
 const HELP: &str = "For documentation check https://github.com/adrianmrit/mom.";
 
@@ -28,23 +41,167 @@ struct TaskSubcommand {
 }
 
 /// Enum of available mom file versions
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub(crate) enum Version {
     #[serde(rename = "1")]
     V1,
 }
 
+/// Names of the OS specific versions a task can have
+const OS_NAMES: [&str; 3] = ["linux", "windows", "macos"];
+
+/// Returns the name of the plain task and the OS of the given OS specific task name, i.e.
+/// `("build", "linux")` for `build.linux`, or None if it is not OS specific.
+fn get_os_task_parts(name: &str) -> Option<(&str, &str)> {
+    OS_NAMES.iter().find_map(|os| {
+        let base_name = name.strip_suffix(os)?.strip_suffix('.')?;
+        Some((base_name, *os))
+    })
+}
+
+/// Format used to print the tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human readable, colored text
+    Text,
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    /// Returns the output format with the given name, defaulting to text
+    fn from_name(name: &str) -> Self {
+        match name {
+            "json" => OutputFormat::Json,
+            "yaml" => OutputFormat::Yaml,
+            _ => OutputFormat::Text,
+        }
+    }
+
+    /// Prints the given value in this format. Text is not supported, as each value is printed
+    /// differently in that case.
+    fn print<T: Serialize>(&self, value: &T) -> DynErrResult<()> {
+        match self {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+            OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+            OutputFormat::Text => return Err("Cannot serialize to text".into()),
+        }
+        Ok(())
+    }
+}
+
+/// Information about a task, printed when using a machine readable format
+#[derive(Serialize)]
+struct TaskSummary {
+    name: String,
+    help: String,
+    private: bool,
+    /// OS specific versions of the task
+    os: Vec<String>,
+    /// Tasks it extends from
+    extend: Vec<String>,
+    has_condition: bool,
+    params: Vec<Param>,
+}
+
+impl TaskSummary {
+    /// Returns the summary of the given task
+    ///
+    /// # Arguments
+    ///
+    /// * `task`: Task to summarize
+    /// * `mom_file`: Mom file the task belongs to, to look for its OS specific versions
+    fn new(task: &Task, mom_file: &MomFile) -> Self {
+        let name = task.get_name();
+        TaskSummary {
+            name: String::from(name),
+            help: String::from(task.get_help()),
+            private: task.is_private(),
+            os: OS_NAMES
+                .iter()
+                .filter(|os| mom_file.tasks.contains_key(&format!("{}.{}", name, os)))
+                .map(|os| String::from(*os))
+                .collect(),
+            extend: task.common.extend.iter().map(String::from).collect(),
+            has_condition: task.has_condition(),
+            params: task.get_params().to_vec(),
+        }
+    }
+}
+
+/// Information about the tasks of a mom file, printed when using a machine readable format
+#[derive(Serialize)]
+struct MomFileSummary {
+    path: String,
+    tasks: Vec<TaskSummary>,
+}
+
+impl MomFileSummary {
+    /// Returns the summary of the given mom file. The OS specific versions of the tasks are
+    /// listed in the `os` field of the task they belong to.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path of the mom file
+    /// * `mom_file`: Mom file to summarize
+    fn new(path: &Path, mom_file: &MomFile) -> Self {
+        let is_os_task = |name: &str| {
+            OS_NAMES.iter().any(|os| {
+                name.strip_suffix(os)
+                    .and_then(|base| base.strip_suffix('.'))
+                    .is_some_and(|base| mom_file.tasks.contains_key(base))
+            })
+        };
+        let mut tasks: Vec<TaskSummary> = mom_file
+            .tasks
+            .values()
+            .filter(|task| !is_os_task(task.get_name()))
+            .map(|task| TaskSummary::new(task, mom_file))
+            .collect();
+        // The tasks of the included files are listed with their namespace, i.e. `backend:test`
+        for (namespace, included) in &mom_file.included {
+            let included_tasks = MomFileSummary::new(&included.filepath, included).tasks;
+            tasks.extend(included_tasks.into_iter().map(|mut task| {
+                task.name = format!("{}:{}", namespace, task.name);
+                task
+            }));
+        }
+        tasks.sort_by(|a, b| a.name.cmp(&b.name));
+        MomFileSummary {
+            path: path.to_string_lossy().to_string(),
+            tasks,
+        }
+    }
+}
+
+/// Information about a single task, printed when using a machine readable format
+#[derive(Serialize)]
+struct TaskInfoSummary {
+    /// Path of the mom file the task belongs to
+    path: String,
+    task: TaskSummary,
+}
+
 /// Argument errors
 #[derive(Debug, PartialEq, Eq)]
 enum ArgsError {
     /// Raised when no task to run is given
     MissingTaskArg,
+    /// Raised when the tasks are given with `--tasks` and as a command at the same time
+    TasksAndCommand,
+    /// Raised when a task is given along with `--init`
+    InitAndCommand,
 }
 
 impl fmt::Display for ArgsError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             ArgsError::MissingTaskArg => write!(f, "No task was given."),
+            ArgsError::TasksAndCommand => write!(
+                f,
+                "Tasks cannot be given with --tasks and as a command at the same time."
+            ),
+            ArgsError::InitAndCommand => write!(f, "A task cannot be given with --init."),
         }
     }
 }
@@ -53,6 +210,8 @@ impl Error for ArgsError {
     fn description(&self) -> &str {
         match *self {
             ArgsError::MissingTaskArg => "no task given",
+            ArgsError::TasksAndCommand => "tasks given with --tasks and as a command",
+            ArgsError::InitAndCommand => "task given with --init",
         }
     }
 
@@ -71,6 +230,44 @@ fn colorize_mom_file_path(val: &str) -> ColoredString {
     val.bright_blue()
 }
 
+/// Prints the parameters of a task, one per line, with their usage aligned
+///
+/// # Arguments
+///
+/// * `params`: Parameters to print
+/// * `prefix`: Prefix of each line
+fn print_params(params: &[Param], prefix: &str) {
+    if params.is_empty() {
+        return;
+    }
+    println!("{}Params:", prefix);
+    let usages: Vec<String> = params.iter().map(|param| param.get_usage()).collect();
+    let width = usages.iter().map(|usage| usage.len()).max().unwrap_or(0);
+    for (param, usage) in params.iter().zip(usages) {
+        let mut details = Vec::new();
+        if let Some(help) = &param.help {
+            details.push(help.trim().green());
+        }
+        if param.required {
+            details.push("(required)".yellow());
+        } else if let Some(default) = &param.default {
+            let default = serde_yaml::to_string(default).unwrap_or_default();
+            details.push(format!("(default: {})", default.trim()).yellow());
+        }
+        if details.is_empty() {
+            println!("{}  {}", prefix, usage.bright_cyan());
+            continue;
+        }
+        let details: Vec<String> = details.iter().map(|detail| detail.to_string()).collect();
+        println!(
+            "{}  {}  {}",
+            prefix,
+            format!("{:width$}", usage).bright_cyan(),
+            details.join(" ")
+        );
+    }
+}
+
 struct Mom {
     mom_files: MomFilesContainer,
 }
@@ -94,8 +291,64 @@ impl Mom {
         Ok(mom_file_ptr)
     }
 
+    /// Returns the error for a task that was not found, suggesting the public tasks with the
+    /// closest names, including the OS specific ones and the ones of included files, and
+    /// listing the mom files that were searched.
+    ///
+    /// # Arguments
+    ///
+    /// * `task`: Name of the task that was not found
+    /// * `searched`: Paths of the mom files searched
+    fn task_not_found(&mut self, task: &str, searched: &[PathBuf]) -> Box<dyn Error> {
+        let mut task_names = Vec::new();
+        for path in searched {
+            // The files were already read, so they come from the cache
+            let mom_file_ptr = match self.mom_files.read_mom_file(path.clone()) {
+                Ok(mom_file_ptr) => mom_file_ptr,
+                Err(_) => continue,
+            };
+            let mom_file_lock = mom_file_ptr.lock().unwrap();
+            for name in mom_file_lock.get_public_task_names() {
+                // OS specific tasks for the current OS also run with the plain name
+                if let Some(base_name) = name.strip_suffix(&format!(".{}", env::consts::OS)) {
+                    task_names.push(String::from(base_name));
+                }
+                task_names.push(String::from(name));
+            }
+            task_names.extend(mom_file_lock.get_included_public_task_names());
+        }
+
+        let mut message = format!("Task {} not found.", task);
+        let suggestions = get_suggestions(task, task_names.iter().map(String::as_str));
+        if !suggestions.is_empty() {
+            message.push_str("\nDid you mean:");
+            for suggestion in suggestions {
+                message.push_str(&format!("\n - {}", suggestion));
+            }
+        }
+        if searched.is_empty() {
+            message.push_str("\nNo mom files found.");
+        } else {
+            message.push_str("\nSearched in:");
+            for path in searched {
+                message.push_str(&format!("\n - {}", path.display()));
+            }
+        }
+        message.into()
+    }
+
     /// prints mom file paths and their tasks
-    fn print_tasks_list(&mut self, paths: PathIterator) -> DynErrResult<()> {
+    fn print_tasks_list(&mut self, paths: PathIterator, format: OutputFormat) -> DynErrResult<()> {
+        if format != OutputFormat::Text {
+            let mut summaries = Vec::new();
+            for path in paths {
+                let mom_file_ptr = self.get_mom_file_lock(path.clone())?;
+                let mom_file_lock = mom_file_ptr.lock().unwrap();
+                summaries.push(MomFileSummary::new(&path, &mom_file_lock));
+            }
+            return format.print(&summaries);
+        }
+
         let mut found = false;
         for path in paths {
             found = true;
@@ -105,6 +358,8 @@ impl Mom {
             println!("{}:", colorize_mom_file_path(&path.to_string_lossy()));
 
             let mut task_names = mom_file_lock.get_public_task_names();
+            let included_task_names = mom_file_lock.get_included_public_task_names();
+            task_names.extend(included_task_names.iter().map(String::as_str));
             task_names.sort();
             if task_names.is_empty() {
                 println!("  {}", "No tasks found.".red());
@@ -120,13 +375,76 @@ impl Mom {
         Ok(())
     }
 
+    /// Prints the names of the public tasks, one per line, to be used by the shell completion
+    /// scripts. Mom files that cannot be read are skipped, as errors cannot be shown while
+    /// completing.
+    fn print_task_names(&mut self, paths: PathIterator) {
+        let mut task_names: Vec<String> = Vec::new();
+        for path in paths {
+            let mom_file_ptr = match self.mom_files.read_mom_file(path) {
+                Ok(mom_file_ptr) => mom_file_ptr,
+                Err(_) => continue,
+            };
+            let mom_file_lock = mom_file_ptr.lock().unwrap();
+            for task in mom_file_lock.get_public_task_names() {
+                task_names.push(String::from(task));
+            }
+            task_names.extend(mom_file_lock.get_included_public_task_names());
+        }
+        // OS specific tasks run with the plain name in their OS, and are not meant for the others
+        let mut task_names: Vec<String> = task_names
+            .into_iter()
+            .filter_map(|name| match get_os_task_parts(&name) {
+                Some((base_name, os)) if os == env::consts::OS => Some(String::from(base_name)),
+                Some(_) => None,
+                None => Some(name),
+            })
+            .collect();
+        task_names.sort();
+        task_names.dedup();
+        for task in task_names {
+            println!("{}", task);
+        }
+    }
+
     /// Prints help for the given task
-    fn print_task_info(&mut self, paths: PathIterator, task: &str) -> DynErrResult<()> {
+    fn print_task_info(
+        &mut self,
+        paths: PathIterator,
+        task: &str,
+        format: OutputFormat,
+    ) -> DynErrResult<()> {
+        let mut searched = Vec::new();
         for path in paths {
+            searched.push(path.clone());
             let mom_file_ptr = self.get_mom_file_lock(path.clone())?;
             let mom_file_lock = mom_file_ptr.lock().unwrap();
 
-            let task = mom_file_lock.clone_task(task);
+            // Tasks of included files are shown with the file they belong to
+            let (mom_file, task_name) = match mom_file_lock.resolve_namespace(task) {
+                Some(resolved) => resolved,
+                None => continue,
+            };
+            let path = &mom_file.filepath;
+
+            if format != OutputFormat::Text {
+                // The OS specific versions are listed in the summary of the plain task
+                let task = mom_file
+                    .tasks
+                    .get(task_name)
+                    .or_else(|| mom_file.get_task(task_name));
+                match task {
+                    Some(task) => {
+                        return format.print(&TaskInfoSummary {
+                            path: path.to_string_lossy().to_string(),
+                            task: TaskSummary::new(task, mom_file),
+                        });
+                    }
+                    None => continue,
+                }
+            }
+
+            let task = mom_file.clone_task(task_name);
 
             match task {
                 Some(task) => {
@@ -149,12 +467,144 @@ impl Mom {
                             )
                         }
                     }
+                    print_params(task.get_params(), prefix);
                     return Ok(());
                 }
                 None => continue,
             }
         }
-        Err(format!("Task {} not found", task).into())
+        Err(self.task_not_found(task, &searched))
+    }
+
+    /// Prints the given task as it would run, with its env and vars merged from every place
+    /// they can be given, along with where each value comes from, and its commands rendered,
+    /// including the ones of the tasks it runs from its commands.
+    fn explain_task(
+        &mut self,
+        paths: PathIterator,
+        task: &str,
+        ctx: &RunContext,
+    ) -> DynErrResult<()> {
+        let mut searched = Vec::new();
+        for path in paths {
+            searched.push(path.clone());
+            let mom_file_ptr = self.get_mom_file_lock(path.clone())?;
+            let mom_file_lock = mom_file_ptr.lock().unwrap();
+
+            // Tasks of included files are explained with the file they belong to
+            let (mom_file, task_name) = match mom_file_lock.resolve_namespace(task) {
+                Some(resolved) => resolved,
+                None => continue,
+            };
+            let task = match mom_file.get_task(task_name) {
+                Some(task) => task,
+                None => continue,
+            };
+            return match TaskExplanation::new(task, mom_file, ctx) {
+                Ok(explanation) => {
+                    println!("{}", explanation);
+                    Ok(())
+                }
+                Err(error) => {
+                    let error = AwareTaskError::new(task.get_name(), error);
+                    Err(FileTaskError::new(mom_file.filepath.clone(), error).into())
+                }
+            };
+        }
+        Err(self.task_not_found(task, &searched))
+    }
+
+    /// Checks the mom files without running their tasks, and prints all the problems found,
+    /// including the ones of the files they include. Fails if any problem is found.
+    fn validate(&mut self, paths: PathIterator) -> DynErrResult<()> {
+        let mut checked: Vec<PathBuf> = Vec::new();
+        let mut problem_count = 0;
+        let mut files_with_problems = 0;
+        for path in paths {
+            for (path, problems) in self.check_mom_file(path) {
+                if checked.contains(&path) {
+                    continue;
+                }
+                checked.push(path.clone());
+                if problems.is_empty() {
+                    continue;
+                }
+                problem_count += problems.len();
+                files_with_problems += 1;
+                eprintln!("{}:", colorize_mom_file_path(&path.to_string_lossy()));
+                for problem in problems {
+                    eprintln!(" - {}", problem.replace('\n', "\n   ").red());
+                }
+            }
+        }
+        if checked.is_empty() {
+            println!("No mom files found.");
+            return Ok(());
+        }
+        let file_word = |count: usize| if count == 1 { "mom file" } else { "mom files" };
+        if problem_count > 0 {
+            let problem_word = if problem_count == 1 {
+                "problem"
+            } else {
+                "problems"
+            };
+            return Err(format!(
+                "Found {} {} in {} {}.",
+                problem_count,
+                problem_word,
+                files_with_problems,
+                file_word(files_with_problems)
+            )
+            .into());
+        }
+        let message = format!(
+            "No problems found in {} {}.",
+            checked.len(),
+            file_word(checked.len())
+        );
+        println!("{}", message.mom_info());
+        Ok(())
+    }
+
+    /// Returns the problems found in the mom file at the given path, and in the files it
+    /// includes, by path. If the file cannot be loaded, it is checked without loading it, as
+    /// loading stops at the first problem. The error it failed with is reported too, unless
+    /// it is one of the problems found.
+    fn check_mom_file(&mut self, path: PathBuf) -> Vec<(PathBuf, Vec<String>)> {
+        let mom_file_ptr = match self.mom_files.read_mom_file(path.clone()) {
+            Ok(mom_file_ptr) => mom_file_ptr,
+            Err(e) => {
+                let problems = MomFile::check_path(&path).unwrap_or_default();
+                let mut descriptions = Vec::new();
+                // Checking may not find it, i.e. a cyclic dependency or a problem in a base file
+                if !problems
+                    .iter()
+                    .any(|problem| problem.is_same_as(e.as_ref()))
+                {
+                    descriptions.push(e.to_string());
+                }
+                descriptions.extend(problems.into_iter().map(|problem| problem.description));
+                return vec![(path, descriptions)];
+            }
+        };
+        let describe = |problems: Vec<Problem>| -> Vec<String> {
+            problems
+                .into_iter()
+                .map(|problem| problem.description)
+                .collect()
+        };
+        let mom_file_lock = mom_file_ptr.lock().unwrap();
+        let mut results = vec![(path, describe(mom_file_lock.check()))];
+        let mut included: Vec<&MomFile> = mom_file_lock
+            .included
+            .values()
+            .map(|m| m.as_ref())
+            .collect();
+        while let Some(mom_file) = included.pop() {
+            results.push((mom_file.filepath.clone(), describe(mom_file.check())));
+            included.extend(mom_file.included.values().map(|m| m.as_ref()));
+        }
+        results
     }
 
     /// Runs the given task
@@ -163,18 +613,92 @@ impl Mom {
         paths: PathIterator,
         task: &str,
         args: &ArgsContext,
-        dry_run: bool,
+        ctx: &RunContext,
     ) -> DynErrResult<()> {
+        let mut searched = Vec::new();
         for path in paths {
+            searched.push(path.clone());
             let mom_file_ptr = self.get_mom_file_lock(path.clone())?;
             let mom_file_lock = mom_file_ptr.lock().unwrap();
 
-            let task = mom_file_lock.clone_public_task(task);
+            let task = find_public_task(&mom_file_lock, task);
 
             match task {
-                Some(task) => {
+                Some((mom_file, task)) => {
+                    let path = mom_file.filepath.clone();
+                    println!("{}", &path.to_string_lossy().mom_info());
+                    return match task.run(args, mom_file, ctx) {
+                        Ok(val) => Ok(val),
+                        Err(error) => Err(FileTaskError::new(path, error).into()),
+                    };
+                }
+                None => continue,
+            }
+        }
+        Err(self.task_not_found(task, &searched))
+    }
+
+    /// Runs the given tasks in order, without arguments. Stops at the first task that fails,
+    /// unless `keep_going` is set, in which case the errors are printed as they happen and the
+    /// remaining tasks still run.
+    ///
+    /// # Arguments
+    ///
+    /// * `get_paths`: Returns the paths to search the tasks in
+    /// * `tasks`: Names of the tasks to run
+    /// * `keep_going`: Whether to run the remaining tasks after one fails
+    /// * `ctx`: Context shared by all the tasks, so that their dependencies only run once
+    fn run_tasks(
+        &mut self,
+        get_paths: impl Fn() -> PathIterator,
+        tasks: &[&String],
+        keep_going: bool,
+        ctx: &RunContext,
+    ) -> DynErrResult<()> {
+        let args = ArgsContext::new();
+        let mut failed = Vec::new();
+        let mut exit_code = None;
+        for task in tasks {
+            // Ctrl-C stops the remaining tasks, even with keep_going
+            ctx.check_can_continue()?;
+            let error = match self.run_task(get_paths(), task, &args, ctx) {
+                Ok(_) => continue,
+                Err(error) => error,
+            };
+            if !keep_going {
+                return Err(error);
+            }
+            eprintln!("{}", error.to_string().mom_error());
+            exit_code.get_or_insert(get_exit_code(error.as_ref()));
+            failed.push(task.to_string());
+        }
+        match exit_code {
+            Some(exit_code) => Err(TasksFailedError { failed, exit_code }.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the given task, and runs it again every time the files it watches change
+    fn watch_task(
+        &mut self,
+        paths: PathIterator,
+        task: &str,
+        args: &ArgsContext,
+        ctx: &RunContext,
+    ) -> DynErrResult<()> {
+        let mut searched = Vec::new();
+        for path in paths {
+            searched.push(path.clone());
+            let mom_file_ptr = self.get_mom_file_lock(path.clone())?;
+            let mom_file_lock = mom_file_ptr.lock().unwrap();
+
+            let task = find_public_task(&mom_file_lock, task);
+
+            match task {
+                Some((mom_file, task)) => {
+                    let path = mom_file.filepath.clone();
                     println!("{}", &path.to_string_lossy().mom_info());
-                    return match task.run(args, &mom_file_lock, dry_run) {
+                    return match watch_task(&task, args, mom_file, ctx) {
                         Ok(val) => Ok(val),
                         Err(e) => {
                             let e = format!("{}:\n{}", &path.to_string_lossy().red(), e);
@@ -185,10 +709,22 @@ impl Mom {
                 None => continue,
             }
         }
-        Err(format!("Task {} not found", task).into())
+        Err(self.task_not_found(task, &searched))
     }
 }
 
+/// Returns a copy of the public task with the given name, along with the mom file it belongs to,
+/// which is an included file if the name is namespaced, i.e. `backend:test`.
+///
+/// # Arguments
+///
+/// * `mom_file`: Mom file to search in
+/// * `task_name`: Name of the task, possibly namespaced
+fn find_public_task<'a>(mom_file: &'a MomFile, task_name: &str) -> Option<(&'a MomFile, Task)> {
+    let (mom_file, task_name) = mom_file.resolve_namespace(task_name)?;
+    Some((mom_file, mom_file.clone_public_task(task_name)?))
+}
+
 // TODO: Handle
 impl TaskSubcommand {
     /// Returns a new TaskSubcommand
@@ -205,6 +741,25 @@ impl TaskSubcommand {
     }
 }
 
+/// Returns the exit code for an error returned by `exec`. If a task failed because a process
+/// it ran failed, this is the exit code of that process, so that the callers of mom can tell
+/// the failures of the process apart. Otherwise, it is 1.
+///
+/// # Arguments
+///
+/// * `error`: Error returned by `exec`
+pub fn get_exit_code(error: &(dyn Error + 'static)) -> i32 {
+    if let Some(error) = error.downcast_ref::<TasksFailedError>() {
+        return error.exit_code;
+    }
+    error
+        .downcast_ref::<FileTaskError>()
+        .and_then(|e| e.error.error.exit_code())
+        // 0 would mean success, i.e. if it was not one of the `ok_exit_codes`
+        .filter(|code| *code != 0)
+        .unwrap_or(1)
+}
+
 /// Executes the program. If errors are encountered during the execution these
 /// are returned immediately. The wrapping method needs to take care of formatting
 /// and displaying these errors appropriately.
@@ -238,12 +793,91 @@ pub fn exec() -> DynErrResult<()> {
                 .help("Displays information about the given task")
                 .value_name("TASK"),
         )
+        // The options that print tasks, which can take a --format
+        .group(clap::ArgGroup::new("task-output").args(["list-tasks", "task-info"]))
+        .arg(
+            clap::Arg::new("format")
+                .long("format")
+                .action(ArgAction::Set)
+                .value_parser(["text", "json", "yaml"])
+                .default_value("text")
+                .requires("task-output")
+                .help("Format used by --list-tasks and --task-info")
+                .value_name("FORMAT"),
+        )
         .arg(
             clap::Arg::new("dry")
                 .long("dry")
                 .action(ArgAction::SetTrue)
                 .help("Runs the task in dry mode, i.e. without executing any commands"),
         )
+        .arg(
+            clap::Arg::new("set")
+                .long("set")
+                .action(ArgAction::Append)
+                .help("Overrides a variable or environment variable of the tasks, i.e. `vars.KEY=VALUE` or `env.KEY=VALUE`")
+                .value_name("KEY=VALUE"),
+        )
+        .arg(
+            clap::Arg::new("watch")
+                .long("watch")
+                .action(ArgAction::SetTrue)
+                .help("Runs the task again every time the files it watches change"),
+        )
+        .arg(
+            clap::Arg::new("tasks")
+                .long("tasks")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .conflicts_with_all(["watch", "list-tasks", "task-info"])
+                .help("Runs the given tasks in order, without arguments, i.e. `--tasks lint,test`")
+                .value_name("TASKS"),
+        )
+        .arg(
+            clap::Arg::new("keep-going")
+                .long("keep-going")
+                .action(ArgAction::SetTrue)
+                .requires("tasks")
+                .help("Runs the remaining tasks given with --tasks after one fails"),
+        )
+        .arg(
+            clap::Arg::new("init")
+                .long("init")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["list", "list-tasks", "task-info", "tasks", "watch"])
+                .help("Creates a mom.root.yml file in the current directory, or the one given with --file or --global"),
+        )
+        .arg(
+            clap::Arg::new("validate")
+                .long("validate")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["list", "list-tasks", "task-info", "tasks", "watch", "init"])
+                .help("Checks the mom files and their tasks without running them, reporting all the problems found"),
+        )
+        .arg(
+            clap::Arg::new("explain")
+                .long("explain")
+                .action(ArgAction::Set)
+                .conflicts_with_all(["list", "list-tasks", "task-info", "tasks", "watch", "init", "validate"])
+                .help("Prints the given task as it would run, with its env and vars and where each value comes from, and its commands rendered")
+                .value_name("TASK"),
+        )
+        .arg(
+            clap::Arg::new("completions")
+                .long("completions")
+                .action(ArgAction::Set)
+                .value_parser(SUPPORTED_SHELLS)
+                .help("Prints the completion script for the given shell")
+                .value_name("SHELL"),
+        )
+        .arg(
+            clap::Arg::new("complete-tasks")
+                .long("complete-tasks")
+                .hide(true)
+                .help("Prints the names of the tasks, used by the completion scripts")
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             clap::Arg::new("file")
                 .short('f')
@@ -262,33 +896,86 @@ pub fn exec() -> DynErrResult<()> {
         );
     let matches = app.get_matches();
 
+    if let Some(shell) = matches.get_one::<String>("completions") {
+        if let Some(script) = get_completion_script(shell) {
+            print!("{}", script);
+        }
+        return Ok(());
+    }
+
     let current_dir = env::current_dir()?;
+
+    if matches.get_one::<bool>("init").cloned().unwrap_or(false) {
+        if matches.subcommand().is_some() {
+            return Err(ArgsError::InitAndCommand.into());
+        }
+        let global = matches.get_one::<bool>("global").cloned().unwrap_or(false);
+        let path = match matches.get_one::<String>("file") {
+            Some(file_path) => current_dir.join(file_path),
+            None if global => match GlobalMomFilePath::new().next() {
+                Some(existing) => existing,
+                None => get_default_global_mom_file_path()
+                    .ok_or("Could not find the home directory.")?,
+            },
+            None => current_dir.join(DEFAULT_MOM_FILE_NAME),
+        };
+        // The tasks of the global file are not tied to a project
+        init_mom_file(&path, !global)?;
+        println!("{}", format!("Created {}", path.display()).mom_info());
+        return Ok(());
+    }
+
     let mut mom = Mom::new();
 
-    let mom_file_paths: PathIterator = match matches.get_one::<String>("file") {
-        None => match matches.get_one::<bool>("global").cloned().unwrap_or(false) {
-            true => GlobalMomFilePath::new(),
-            false => MomFilePaths::new(&current_dir),
-        },
-        Some(file_path) => SingleMomFilePath::new(file_path),
+    // The paths are searched again for each task when running several of them
+    let get_mom_file_paths = || -> PathIterator {
+        match matches.get_one::<String>("file") {
+            None => match matches.get_one::<bool>("global").cloned().unwrap_or(false) {
+                true => GlobalMomFilePath::new(),
+                false => MomFilePaths::new(&current_dir),
+            },
+            Some(file_path) => SingleMomFilePath::new(file_path),
+        }
     };
+    let mom_file_paths = get_mom_file_paths();
 
     let dry_run = matches.get_one::<bool>("dry").cloned().unwrap_or(false);
+    let format = match matches.get_one::<String>("format") {
+        Some(format) => OutputFormat::from_name(format),
+        None => OutputFormat::Text,
+    };
+
+    if matches
+        .get_one::<bool>("complete-tasks")
+        .cloned()
+        .unwrap_or(false)
+    {
+        mom.print_task_names(mom_file_paths);
+        return Ok(());
+    };
 
     if matches
         .get_one::<bool>("list-tasks")
         .cloned()
         .unwrap_or(false)
     {
-        mom.print_tasks_list(mom_file_paths)?;
+        mom.print_tasks_list(mom_file_paths, format)?;
         return Ok(());
     };
 
     if let Some(task_name) = matches.get_one::<String>("task-info") {
-        mom.print_task_info(mom_file_paths, task_name)?;
+        mom.print_task_info(mom_file_paths, task_name, format)?;
         return Ok(());
     };
 
+    if matches
+        .get_one::<bool>("validate")
+        .cloned()
+        .unwrap_or(false)
+    {
+        return mom.validate(mom_file_paths);
+    }
+
     if matches.get_one::<bool>("list").cloned().unwrap_or(false) {
         for path in mom_file_paths {
             println!("{}", colorize_mom_file_path(&path.to_string_lossy()));
@@ -296,12 +983,54 @@ pub fn exec() -> DynErrResult<()> {
         return Ok(());
     }
 
+    let overrides: Vec<&String> = match matches.get_many::<String>("set") {
+        Some(values) => values.collect(),
+        None => Vec::new(),
+    };
+    let ctx = RunContext::new(dry_run).with_overrides(Overrides::parse(&overrides)?);
+
+    if let Some(task_name) = matches.get_one::<String>("explain") {
+        return mom.explain_task(mom_file_paths, task_name, &ctx);
+    }
+
+    if let Some(tasks) = matches.get_many::<String>("tasks") {
+        if matches.subcommand().is_some() {
+            return Err(ArgsError::TasksAndCommand.into());
+        }
+        let tasks: Vec<&String> = tasks.collect();
+        let keep_going = matches
+            .get_one::<bool>("keep-going")
+            .cloned()
+            .unwrap_or(false);
+        let result = mom.run_tasks(get_mom_file_paths, &tasks, keep_going, &ctx);
+        print_dry_run_message(&result, &ctx);
+        return result;
+    }
+
     let task_command = TaskSubcommand::new(&matches)?;
 
-    mom.run_task(
+    if matches.get_one::<bool>("watch").cloned().unwrap_or(false) {
+        return mom.watch_task(
+            mom_file_paths,
+            &task_command.task,
+            &task_command.args_context,
+            &ctx,
+        );
+    }
+
+    let result = mom.run_task(
         mom_file_paths,
         &task_command.task,
         &task_command.args_context,
-        dry_run,
-    )
+        &ctx,
+    );
+    print_dry_run_message(&result, &ctx);
+    result
+}
+
+/// Prints that nothing was executed after the plan of a successful dry run.
+fn print_dry_run_message(result: &DynErrResult<()>, ctx: &RunContext) {
+    if ctx.dry_run && result.is_ok() {
+        println!("{}", DRY_RUN_MESSAGE.mom_info());
+    }
 }
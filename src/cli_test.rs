@@ -28,3 +28,146 @@ fn test_list() -> Result<(), Box<dyn std::error::Error>> {
     );
     Ok(())
 }
+
+#[test]
+fn test_complete_tasks() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = TempDir::new().unwrap();
+    let tmp_dir_path = tmp_dir.path();
+    std::fs::write(
+        tmp_dir_path.join("mom.root.yml"),
+        format!(
+            r#"
+version: 1
+
+tasks:
+  build:
+    script: echo build
+  bench:
+    script: echo bench
+  setup:
+    private: true
+    script: echo setup
+  build.linux:
+    script: echo build linux
+  build.windows:
+    script: echo build windows
+  build.macos:
+    script: echo build macos
+  lint.{os}:
+    script: echo lint
+"#,
+            os = std::env::consts::OS
+        ),
+    )?;
+    std::fs::write(
+        tmp_dir_path.join("other.yml"),
+        r#"
+version: 1
+
+tasks:
+  other:
+    script: echo other
+"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin("mom")?;
+    cmd.current_dir(tmp_dir_path);
+    cmd.arg("--complete-tasks");
+    // OS specific tasks are completed with the name they run with
+    cmd.assert().success().stdout("bench\nbuild\nlint\n");
+
+    let mut cmd = Command::cargo_bin("mom")?;
+    cmd.current_dir(tmp_dir_path);
+    cmd.args(["-f", "other.yml", "--complete-tasks"]);
+    cmd.assert().success().stdout("other\n");
+    Ok(())
+}
+
+#[test]
+fn test_completions() -> Result<(), Box<dyn std::error::Error>> {
+    for shell in ["bash", "zsh", "fish"] {
+        let mut cmd = Command::cargo_bin("mom")?;
+        cmd.args(["--completions", shell]);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("--complete-tasks"));
+    }
+
+    let mut cmd = Command::cargo_bin("mom")?;
+    cmd.args(["--completions", "powershell"]);
+    cmd.assert().failure();
+    Ok(())
+}
+
+#[test]
+fn test_list_tasks_json() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = TempDir::new().unwrap();
+    let tmp_dir_path = tmp_dir.path();
+    let mom_file_path = tmp_dir_path.join("mom.root.yml");
+    std::fs::write(
+        &mom_file_path,
+        r#"
+version: 1
+
+tasks:
+  base:
+    private: true
+    script: echo base
+  build:
+    help: Builds the project
+    extend: base
+    condition: "true"
+    linux:
+      script: echo linux
+    windows:
+      script: echo windows
+"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin("mom")?;
+    cmd.current_dir(tmp_dir_path);
+    cmd.args(["-t", "--format", "json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output: serde_json::Value = serde_json::from_slice(&output)?;
+    let expected = serde_json::json!([{
+        "path": mom_file_path.to_string_lossy(),
+        "tasks": [
+            {
+                "name": "base",
+                "help": "",
+                "private": true,
+                "os": [],
+                "extend": [],
+                "has_condition": false,
+                "params": []
+            },
+            {
+                "name": "build",
+                "help": "Builds the project",
+                "private": false,
+                "os": ["linux", "windows"],
+                "extend": ["base"],
+                "has_condition": true,
+                "params": []
+            }
+        ]
+    }]);
+    assert_eq!(output, expected);
+
+    let mut cmd = Command::cargo_bin("mom")?;
+    cmd.current_dir(tmp_dir_path);
+    cmd.args(["-i", "build", "--format", "yaml"]);
+    cmd.assert().success().stdout(
+        predicate::str::contains("name: build\n")
+            .and(predicate::str::contains("os:\n  - linux\n  - windows\n")),
+    );
+
+    // The format only applies to the options that print tasks
+    let mut cmd = Command::cargo_bin("mom")?;
+    cmd.current_dir(tmp_dir_path);
+    cmd.args(["--format", "json", "build"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "the following required arguments were not provided",
+    ));
+    Ok(())
+}
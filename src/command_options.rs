@@ -0,0 +1,195 @@
+#[cfg(test)]
+#[path = "command_options_test.rs"]
+mod command_options_test;
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::{de, Deserialize, Serialize};
+
+use crate::inherit_option_value;
+
+/// Delay before the first retry, if `retry_delay` is not given
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Duration given as a number followed by a unit, i.e. `500ms`, `30s`, `5m` or `1h`, or as a
+/// number of seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DurationValue(pub(crate) Duration);
+
+impl DurationValue {
+    /// Parses a duration, i.e. `1.5s`. On error, returns a description of the problem.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: Value to parse
+    pub(crate) fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let unit_start = value
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(value.len());
+        let (number, unit) = value.split_at(unit_start);
+        let seconds_per_unit = match unit.trim() {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return Err(Self::invalid_message(value)),
+        };
+        number
+            .parse::<f64>()
+            .ok()
+            .and_then(|number| Duration::try_from_secs_f64(number * seconds_per_unit).ok())
+            .map(DurationValue)
+            .ok_or_else(|| Self::invalid_message(value))
+    }
+
+    fn invalid_message(value: &str) -> String {
+        format!(
+            "Invalid duration `{}`, expected a number followed by ms, s, m or h, i.e. `30s`.",
+            value
+        )
+    }
+}
+
+impl fmt::Display for DurationValue {
+    // `is_multiple_of` needs Rust 1.87
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let millis = self.0.as_millis();
+        if millis == 0 || millis % 1000 != 0 {
+            write!(f, "{}ms", millis)
+        } else if millis % 3_600_000 == 0 {
+            write!(f, "{}h", millis / 3_600_000)
+        } else if millis % 60_000 == 0 {
+            write!(f, "{}m", millis / 60_000)
+        } else {
+            write!(f, "{}s", millis / 1000)
+        }
+    }
+}
+
+impl Serialize for DurationValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for DurationValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawDuration {
+            Seconds(u64),
+            Text(String),
+        }
+
+        match RawDuration::deserialize(deserializer)? {
+            RawDuration::Seconds(seconds) => Ok(DurationValue(Duration::from_secs(seconds))),
+            RawDuration::Text(text) => DurationValue::parse(&text).map_err(de::Error::custom),
+        }
+    }
+}
+
+/// Options that control how a command runs. They can be set in a task, applying to all of its
+/// commands, or in an inline `cmd` map, taking precedence over the ones of the task.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub(crate) struct CommandOptions {
+    /// Whether to continue if the command fails, reporting the error as a warning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) ignore_error: Option<bool>,
+
+    /// Exit codes considered successful. Defaults to 0 only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) ok_exit_codes: Option<Vec<i32>>,
+
+    /// How many times to run the command again if it fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) retries: Option<u32>,
+
+    /// Time to wait before the first retry. Defaults to one second
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) retry_delay: Option<DurationValue>,
+
+    /// Factor the delay is multiplied by after each retry. Defaults to 1, i.e. a fixed delay
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) retry_backoff: Option<f64>,
+
+    /// Time each run of the command can take, after which it is terminated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout: Option<DurationValue>,
+}
+
+impl CommandOptions {
+    /// Returns whether no option is set.
+    pub(crate) fn is_empty(&self) -> bool {
+        self == &CommandOptions::default()
+    }
+
+    /// Returns a copy of the options, taking the ones that are not set from `defaults`.
+    ///
+    /// # Arguments
+    ///
+    /// * `defaults`: Options to inherit, usually the ones of the task
+    pub(crate) fn with_defaults(&self, defaults: &CommandOptions) -> CommandOptions {
+        let mut options = self.clone();
+        inherit_option_value!(options.ignore_error, defaults.ignore_error);
+        inherit_option_value!(options.ok_exit_codes, defaults.ok_exit_codes);
+        inherit_option_value!(options.retries, defaults.retries);
+        inherit_option_value!(options.retry_delay, defaults.retry_delay);
+        inherit_option_value!(options.retry_backoff, defaults.retry_backoff);
+        inherit_option_value!(options.timeout, defaults.timeout);
+        options
+    }
+
+    /// Validates the options. On error, returns a description of the problem.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self.retry_backoff {
+            Some(backoff) if !(backoff.is_finite() && backoff >= 1.0) => Err(format!(
+                "Invalid retry_backoff `{}`, expected a number greater than or equal to 1.",
+                backoff
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns how many times to run the command again if it fails.
+    pub(crate) fn get_retries(&self) -> u32 {
+        self.retries.unwrap_or(0)
+    }
+
+    /// Returns the time to wait before the given retry.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry`: Number of the retry, starting at 0
+    pub(crate) fn get_retry_delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .retry_delay
+            .map(|delay| delay.0)
+            .unwrap_or(DEFAULT_RETRY_DELAY);
+        let backoff = self.retry_backoff.unwrap_or(1.0);
+        // Saturates instead of overflowing after many retries
+        Duration::try_from_secs_f64(delay.as_secs_f64() * backoff.powi(retry as i32))
+            .unwrap_or(Duration::MAX)
+    }
+
+    /// Returns whether a failure of the command should be reported as a warning instead.
+    pub(crate) fn ignores_error(&self) -> bool {
+        self.ignore_error.unwrap_or(false)
+    }
+
+    /// Returns whether the command succeeded if it exited with the given code.
+    pub(crate) fn is_ok_exit_code(&self, code: i32) -> bool {
+        match &self.ok_exit_codes {
+            Some(codes) => codes.contains(&code),
+            None => code == 0,
+        }
+    }
+}
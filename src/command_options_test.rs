@@ -0,0 +1,117 @@
+use super::*;
+
+#[test]
+fn test_with_defaults() {
+    let defaults = CommandOptions {
+        ignore_error: Some(true),
+        ok_exit_codes: Some(vec![0, 1]),
+        ..Default::default()
+    };
+    let options = CommandOptions {
+        ignore_error: Some(false),
+        ..Default::default()
+    };
+    assert_eq!(
+        options.with_defaults(&defaults),
+        CommandOptions {
+            ignore_error: Some(false),
+            ok_exit_codes: Some(vec![0, 1]),
+            ..Default::default()
+        }
+    );
+    assert!(CommandOptions::default()
+        .with_defaults(&defaults)
+        .ignores_error());
+}
+
+#[test]
+fn test_is_ok_exit_code() {
+    let options = CommandOptions::default();
+    assert!(options.is_ok_exit_code(0));
+    assert!(!options.is_ok_exit_code(1));
+
+    let options = CommandOptions {
+        ok_exit_codes: Some(vec![1, 2]),
+        ..Default::default()
+    };
+    assert!(!options.is_ok_exit_code(0));
+    assert!(options.is_ok_exit_code(1));
+    assert!(options.is_ok_exit_code(2));
+}
+
+#[test]
+fn test_get_retry_delay() {
+    let options = CommandOptions::default();
+    assert_eq!(options.get_retries(), 0);
+    assert_eq!(options.get_retry_delay(0), Duration::from_secs(1));
+    assert_eq!(options.get_retry_delay(3), Duration::from_secs(1));
+
+    let options = CommandOptions {
+        retries: Some(3),
+        retry_delay: Some(DurationValue(Duration::from_millis(500))),
+        retry_backoff: Some(2.0),
+        ..Default::default()
+    };
+    assert_eq!(options.get_retries(), 3);
+    assert_eq!(options.get_retry_delay(0), Duration::from_millis(500));
+    assert_eq!(options.get_retry_delay(1), Duration::from_secs(1));
+    assert_eq!(options.get_retry_delay(2), Duration::from_secs(2));
+    assert_eq!(options.get_retry_delay(10_000), Duration::MAX);
+}
+
+#[test]
+fn test_validate() {
+    let options = CommandOptions {
+        retry_backoff: Some(0.5),
+        ..Default::default()
+    };
+    assert_eq!(
+        options.validate().unwrap_err(),
+        "Invalid retry_backoff `0.5`, expected a number greater than or equal to 1."
+    );
+    assert!(CommandOptions::default().validate().is_ok());
+}
+
+#[test]
+fn test_parse_duration() {
+    let cases = [
+        ("500ms", Duration::from_millis(500)),
+        ("30s", Duration::from_secs(30)),
+        ("1.5s", Duration::from_millis(1500)),
+        ("5m", Duration::from_secs(300)),
+        ("2h", Duration::from_secs(7200)),
+        (" 10 s ", Duration::from_secs(10)),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(DurationValue::parse(value).unwrap().0, expected);
+    }
+
+    for value in ["", "10", "s", "-1s", "10d", "1.2.3s"] {
+        assert_eq!(
+            DurationValue::parse(value).unwrap_err(),
+            format!(
+                "Invalid duration `{}`, expected a number followed by ms, s, m or h, i.e. `30s`.",
+                value.trim()
+            )
+        );
+    }
+
+    let duration: DurationValue = serde_yaml::from_str("20").unwrap();
+    assert_eq!(duration.0, Duration::from_secs(20));
+    let duration: DurationValue = serde_yaml::from_str("20m").unwrap();
+    assert_eq!(duration.0, Duration::from_secs(1200));
+}
+
+#[test]
+fn test_display_duration() {
+    let cases = [
+        (Duration::from_millis(0), "0ms"),
+        (Duration::from_millis(1500), "1500ms"),
+        (Duration::from_secs(30), "30s"),
+        (Duration::from_secs(300), "5m"),
+        (Duration::from_secs(7200), "2h"),
+    ];
+    for (duration, expected) in cases {
+        assert_eq!(DurationValue(duration).to_string(), expected);
+    }
+}
@@ -0,0 +1,179 @@
+/// Completion script for bash
+const BASH_COMPLETIONS: &str = r##"_mom() {
+    local cur prev i
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local opts="-l --list -t --list-tasks -i --task-info --dry --set --watch --tasks --keep-going --init --validate --explain --format -f --file -g --global --completions -h --help -V --version"
+
+    # Options that change where the tasks are read from
+    local source_args=()
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case "${COMP_WORDS[i]}" in
+            -g|--global) source_args+=(--global) ;;
+            -f|--file) source_args+=(--file "${COMP_WORDS[i+1]}"); ((i++)) ;;
+            -i|--task-info|--explain|--tasks|--format|--set|--completions) ((i++)) ;;
+            -*) ;;
+            # A task was already given, the rest are its arguments
+            *) COMPREPLY=($(compgen -f -- "$cur")); return 0 ;;
+        esac
+    done
+
+    case "$prev" in
+        -f|--file)
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return 0
+            ;;
+        --completions)
+            COMPREPLY=($(compgen -W "bash zsh fish" -- "$cur"))
+            return 0
+            ;;
+        --format)
+            COMPREPLY=($(compgen -W "text json yaml" -- "$cur"))
+            return 0
+            ;;
+        --set)
+            # KEY=VALUE cannot be completed
+            COMPREPLY=()
+            return 0
+            ;;
+        --tasks)
+            # Only the task after the last comma is completed, keeping the ones before it
+            local prefix=""
+            [[ "$cur" == *,* ]] && prefix="${cur%,*},"
+            COMPREPLY=($(compgen -P "$prefix" -W "$(mom "${source_args[@]}" --complete-tasks 2>/dev/null)" -- "${cur##*,}"))
+            return 0
+            ;;
+    esac
+
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "$opts" -- "$cur"))
+    else
+        COMPREPLY=($(compgen -W "$(mom "${source_args[@]}" --complete-tasks 2>/dev/null)" -- "$cur"))
+    fi
+}
+
+complete -F _mom -o bashdefault -o default mom
+"##;
+
+/// Completion script for zsh
+const ZSH_COMPLETIONS: &str = r##"#compdef mom
+
+_mom_tasks() {
+    local -a source_args tasks
+    local i
+    for ((i = 2; i < CURRENT; i++)); do
+        case "${words[i]}" in
+            -g|--global) source_args+=(--global) ;;
+            -f|--file) source_args+=(--file "${words[i+1]}") ;;
+        esac
+    done
+    tasks=(${(f)"$(mom "${source_args[@]}" --complete-tasks 2>/dev/null)"})
+    compadd -a tasks
+}
+
+_arguments -s \
+    '(-l --list)'{-l,--list}'[Lists configuration files that can be reached from the current directory]' \
+    '(-t --list-tasks -i --task-info)'{-t,--list-tasks}'[Lists tasks]' \
+    '(-t --list-tasks -i --task-info)'{-i,--task-info}'[Displays information about the given task]:task:_mom_tasks' \
+    '--dry[Runs the task in dry mode, i.e. without executing any commands]' \
+    '*--set[Overrides a variable or environment variable of the tasks, i.e. vars.KEY=VALUE or env.KEY=VALUE]:KEY=VALUE: ' \
+    '--watch[Runs the task again every time the files it watches change]' \
+    '*--tasks[Runs the given tasks in order, without arguments]:tasks:_sequence _mom_tasks' \
+    '--keep-going[Runs the remaining tasks given with --tasks after one fails]' \
+    '--init[Creates a mom.root.yml file in the current directory, or the one given with --file or --global]' \
+    '--validate[Checks the mom files and their tasks without running them, reporting all the problems found]' \
+    '--explain[Prints the given task as it would run, with its env and vars and where each value comes from, and its commands rendered]:task:_mom_tasks' \
+    '--format[Format used by --list-tasks and --task-info]:format:(text json yaml)' \
+    '(-f --file -g --global)'{-f,--file}'[Search for tasks in the given file]:file:_files' \
+    '(-f --file -g --global)'{-g,--global}'[Search for tasks in ~/mom/mom.global.{yml,yaml}]' \
+    '--completions[Prints the completion script for the given shell]:shell:(bash zsh fish)' \
+    '(- *)'{-h,--help}'[Print help]' \
+    '(- *)'{-V,--version}'[Print version]' \
+    '1:task:_mom_tasks' \
+    '*::arguments:_files'
+"##;
+
+/// Completion script for fish
+const FISH_COMPLETIONS: &str = r##"function __mom_complete_tasks
+    set -l tokens (commandline -opc)
+    set -l source_args
+    for i in (seq 2 (count $tokens))
+        switch $tokens[$i]
+            case -g --global
+                set -a source_args --global
+            case -f --file
+                set -l next (math $i + 1)
+                if test $next -le (count $tokens)
+                    set -a source_args --file $tokens[$next]
+                end
+        end
+    end
+    mom $source_args --complete-tasks 2>/dev/null
+end
+
+# Completes the task after the last comma of --tasks, keeping the ones before it
+function __mom_complete_task_list
+    set -l prefix (string replace -r '[^,]*$' '' -- (commandline -ct))
+    for task in (__mom_complete_tasks)
+        echo $prefix$task
+    end
+end
+
+# Returns whether a task was not given yet, as the rest are its arguments
+function __mom_needs_task
+    set -l tokens (commandline -opc)
+    set -l skip 0
+    for token in $tokens[2..-1]
+        if test $skip -eq 1
+            set skip 0
+            continue
+        end
+        switch $token
+            case -f --file -i --task-info --explain --tasks --format --set --completions
+                set skip 1
+            case '-*'
+            case '*'
+                return 1
+        end
+    end
+    return 0
+end
+
+complete -c mom -n __mom_needs_task -f -a '(__mom_complete_tasks)'
+complete -c mom -n __mom_needs_task -s l -l list -d 'Lists configuration files that can be reached from the current directory'
+complete -c mom -n __mom_needs_task -s t -l list-tasks -d 'Lists tasks'
+complete -c mom -n __mom_needs_task -s i -l task-info -x -a '(__mom_complete_tasks)' -d 'Displays information about the given task'
+complete -c mom -n __mom_needs_task -l dry -d 'Runs the task in dry mode, i.e. without executing any commands'
+complete -c mom -n __mom_needs_task -l set -x -d 'Overrides a variable or environment variable of the tasks, i.e. vars.KEY=VALUE or env.KEY=VALUE'
+complete -c mom -n __mom_needs_task -l watch -d 'Runs the task again every time the files it watches change'
+complete -c mom -n __mom_needs_task -l tasks -x -a '(__mom_complete_task_list)' -d 'Runs the given tasks in order, without arguments'
+complete -c mom -n __mom_needs_task -l keep-going -d 'Runs the remaining tasks given with --tasks after one fails'
+complete -c mom -n __mom_needs_task -l init -d 'Creates a mom.root.yml file in the current directory, or the one given with --file or --global'
+complete -c mom -n __mom_needs_task -l validate -d 'Checks the mom files and their tasks without running them, reporting all the problems found'
+complete -c mom -n __mom_needs_task -l explain -x -a '(__mom_complete_tasks)' -d 'Prints the given task as it would run, with its env and vars and where each value comes from, and its commands rendered'
+complete -c mom -n __mom_needs_task -l format -x -a 'text json yaml' -d 'Format used by --list-tasks and --task-info'
+complete -c mom -n __mom_needs_task -s f -l file -r -F -d 'Search for tasks in the given file'
+complete -c mom -n __mom_needs_task -s g -l global -d 'Search for tasks in ~/mom/mom.global.{yml,yaml}'
+complete -c mom -n __mom_needs_task -l completions -x -a 'bash zsh fish' -d 'Prints the completion script for the given shell'
+complete -c mom -n __mom_needs_task -s h -l help -d 'Print help'
+complete -c mom -n __mom_needs_task -s V -l version -d 'Print version'
+"##;
+
+/// Shells supported by `--completions`.
+pub(crate) const SUPPORTED_SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+/// Returns the completion script for the given shell, if supported. Tasks are external
+/// subcommands, so their names cannot be known beforehand. Instead, the scripts call
+/// `mom --complete-tasks` to get them when completing.
+///
+/// # Arguments
+///
+/// * `shell`: Name of the shell, one of `SUPPORTED_SHELLS`
+pub(crate) fn get_completion_script(shell: &str) -> Option<&'static str> {
+    match shell {
+        "bash" => Some(BASH_COMPLETIONS),
+        "zsh" => Some(ZSH_COMPLETIONS),
+        "fish" => Some(FISH_COMPLETIONS),
+        _ => None,
+    }
+}
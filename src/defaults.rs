@@ -1,4 +1,4 @@
-/// Returns true, for serde deserialization defaults
+// Returns true, for serde deserialization defaults
 // pub(crate) fn default_true() -> bool {
 //     true
 // }
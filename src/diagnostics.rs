@@ -0,0 +1,365 @@
+#[cfg(test)]
+#[path = "diagnostics_test.rs"]
+mod diagnostics_test;
+
+use std::error::Error;
+use std::path::Path;
+use std::{fmt, fs};
+
+use crate::errors::{AwareTaskError, TaskError};
+
+/// Names of the options holding commands, which are followed by the index of a command
+const CMDS_OPTIONS: [&str; 2] = ["cmds", "finally"];
+
+/// Names of the OS specific versions a task can have
+const OS_NAMES: [&str; 3] = ["linux", "windows", "macos"];
+
+/// Position in a file, where both the line and the column start from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Position {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+/// Error pointing to where it happened in a file, with a snippet of the line
+#[derive(Debug)]
+pub(crate) struct DiagnosticError {
+    /// Description of the error
+    message: String,
+    /// Location and line of the file the error happened at
+    snippet: String,
+    /// Error that was located, if any, so that it can still be told apart
+    cause: Option<Box<dyn Error>>,
+}
+
+impl DiagnosticError {
+    /// Creates a new DiagnosticError
+    ///
+    /// # Arguments
+    ///
+    /// * `message`: Description of the error
+    /// * `path`: Path of the file the error happened in
+    /// * `source`: Content of the file
+    /// * `position`: Where the error happened in the file
+    pub(crate) fn new(message: String, path: &Path, source: &str, position: Position) -> Self {
+        DiagnosticError {
+            message,
+            snippet: render_snippet(path, source, position),
+            cause: None,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\n{}", self.message, self.snippet)
+    }
+}
+
+impl Error for DiagnosticError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_deref()
+    }
+}
+
+/// Returns the location and the line of the file at the given position, with a caret under
+/// the column, like rustc does. A position at line or column 0 is taken as the first one.
+///
+/// # Arguments
+///
+/// * `path`: Path of the file
+/// * `source`: Content of the file
+/// * `position`: Position to point to
+pub(crate) fn render_snippet(path: &Path, source: &str, position: Position) -> String {
+    let line = source
+        .lines()
+        .nth(position.line.saturating_sub(1))
+        .unwrap_or_default();
+    let line_number = position.line.to_string();
+    let gutter = " ".repeat(line_number.len());
+    format!(
+        "{gutter}--> {}:{}:{}\n{gutter} |\n{} | {}\n{gutter} | {}^",
+        path.display(),
+        position.line,
+        position.column,
+        line_number,
+        line,
+        " ".repeat(position.column.saturating_sub(1))
+    )
+}
+
+/// Returns the error of a mom file that could not be parsed, pointing to where it happened if
+/// the YAML parser knows it. Fields of tasks, which are flattened, are pointed to by the start
+/// of the task instead.
+///
+/// # Arguments
+///
+/// * `error`: Error of the YAML parser
+/// * `path`: Path of the mom file
+/// * `source`: Content of the mom file
+pub(crate) fn from_yaml_error(
+    error: serde_yaml::Error,
+    path: &Path,
+    source: &str,
+) -> Box<dyn Error> {
+    let location = match error.location() {
+        // The line is 0 if the error is not at a line of the file, i.e. for empty documents
+        Some(location) if location.line() > 0 => location,
+        _ => return error.into(),
+    };
+    // The location is shown in the snippet instead
+    let location_text = format!(" at line {} column {}", location.line(), location.column());
+    let message = error.to_string().replacen(&location_text, "", 1);
+    let position = Position {
+        line: location.line(),
+        column: location.column(),
+    };
+    DiagnosticError::new(message, path, source, position).into()
+}
+
+/// Returns the error of a mom file that could not be set up, pointing to where it happened if
+/// it was caused by one of its tasks.
+///
+/// # Arguments
+///
+/// * `error`: Error setting up the mom file
+/// * `path`: Path of the mom file
+pub(crate) fn from_setup_error(error: Box<dyn Error>, path: &Path) -> Box<dyn Error> {
+    let position = match (
+        error.downcast_ref::<AwareTaskError>(),
+        fs::read_to_string(path),
+    ) {
+        (Some(task_error), Ok(source)) => {
+            find_error_position(&source, task_error).map(|position| (source, position))
+        }
+        _ => None,
+    };
+    match position {
+        Some((source, position)) => {
+            let mut diagnostic = DiagnosticError::new(error.to_string(), path, &source, position);
+            diagnostic.cause = Some(error);
+            diagnostic.into()
+        }
+        None => error,
+    }
+}
+
+/// Line of a YAML file, with the indentation taken apart
+#[derive(Clone, Copy)]
+struct YamlLine<'a> {
+    /// Index of the line, starting from 0
+    index: usize,
+    /// Number of characters before the text
+    indent: usize,
+    /// Text of the line, without the indentation
+    text: &'a str,
+}
+
+/// Returns the lines with content, skipping empty lines and comments.
+fn get_yaml_lines(source: &str) -> Vec<YamlLine<'_>> {
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let text = line.trim_start();
+            if text.is_empty() || text.starts_with('#') {
+                return None;
+            }
+            Some(YamlLine {
+                index,
+                indent: line.len() - text.len(),
+                text,
+            })
+        })
+        .collect()
+}
+
+/// Returns the lines nested under the line at `start`, including the rest of that line after
+/// `skip` characters, if any. This way the content of a key or a sequence item can be given in
+/// the same line, i.e. `- cmd: echo`.
+fn get_nested_lines<'a>(lines: &[YamlLine<'a>], start: usize, skip: usize) -> Vec<YamlLine<'a>> {
+    let line = lines[start];
+    let mut nested = Vec::new();
+    let rest = line.text[skip..].trim_start();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        nested.push(YamlLine {
+            index: line.index,
+            indent: line.indent + line.text.len() - rest.len(),
+            text: rest,
+        });
+    }
+    // The items of a sequence can have the same indentation as its key
+    let is_item = |line: &YamlLine| line.text == "-" || line.text.starts_with("- ");
+    let nests_items = !is_item(&line);
+    nested.extend(
+        lines[start + 1..]
+            .iter()
+            .take_while(|nested_line| {
+                nested_line.indent > line.indent
+                    || (nests_items && nested_line.indent == line.indent && is_item(nested_line))
+            })
+            .copied(),
+    );
+    nested
+}
+
+/// Returns the index of the line with the given key in the mapping formed by `lines`, and
+/// the length of the key, including the colon.
+fn find_key(lines: &[YamlLine], key: &str) -> Option<(usize, usize)> {
+    let indent = lines.first()?.indent;
+    let quoted = [
+        format!("{}:", key),
+        format!("\"{}\":", key),
+        format!("'{}':", key),
+    ];
+    lines.iter().enumerate().find_map(|(i, line)| {
+        if line.indent != indent {
+            return None;
+        }
+        quoted.iter().find_map(|quoted| {
+            let rest = line.text.strip_prefix(quoted.as_str())?;
+            (rest.is_empty() || rest.starts_with([' ', '#'])).then_some((i, quoted.len()))
+        })
+    })
+}
+
+/// Returns the index of the nth item of the sequence formed by `lines`.
+fn find_item(lines: &[YamlLine], n: usize) -> Option<usize> {
+    let indent = lines.first()?.indent;
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| {
+            line.indent == indent && (line.text == "-" || line.text.starts_with("- "))
+        })
+        .nth(n)
+        .map(|(i, _)| i)
+}
+
+/// Returns where the value at the given path is declared in a YAML file. Only block style
+/// mappings and sequences are followed, so None is returned if any component of the path is
+/// not literally found, i.e. because a value is given in flow style, like `[a, b]`, or through
+/// an alias or a merge key, rather than guessing a related position.
+///
+/// # Arguments
+///
+/// * `source`: Content of the YAML file
+/// * `path`: Keys and indexes leading to the value, i.e. `["tasks", "build", "cmds", "0"]`
+pub(crate) fn find_yaml_path(source: &str, path: &[String]) -> Option<Position> {
+    let mut lines = get_yaml_lines(source);
+    let mut found = None;
+    for component in path.iter() {
+        let (i, skip) = match component.parse::<usize>() {
+            Ok(n) => {
+                // Commands of parallel groups are given under the `parallel` key
+                let parallel = find_key(&lines, "parallel")
+                    .filter(|_| !lines.first().is_some_and(|line| line.text.starts_with('-')));
+                if let Some((i, skip)) = parallel {
+                    lines = get_nested_lines(&lines, i, skip);
+                }
+                (find_item(&lines, n)?, 1)
+            }
+            Err(_) => find_key(&lines, component)?,
+        };
+        let line = lines[i];
+        let nested = get_nested_lines(&lines, i, skip);
+        // Items point to their value if it is in the same line, rather than to the dash
+        let indent = match nested.first() {
+            Some(first) if skip == 1 && first.index == line.index => first.indent,
+            _ => line.indent,
+        };
+        found = Some(Position {
+            line: line.index + 1,
+            column: indent + 1,
+        });
+        lines = nested;
+    }
+    found
+}
+
+/// Returns the path of a task in the mom file, given the name it runs with, i.e. `build`,
+/// `build.linux` or `build.cmds.2` for a task given in the `cmds` of another. As OS specific
+/// tasks can be declared as `build.linux` or under the `linux` key of `build`, both paths are
+/// returned in that case. Returns an empty list for tasks of included files.
+///
+/// # Arguments
+///
+/// * `task_name`: Name the task runs with
+fn get_task_paths(task_name: &str) -> Vec<Vec<String>> {
+    if task_name.contains(':') {
+        return Vec::new();
+    }
+    let components: Vec<&str> = task_name.split('.').collect();
+    let name = components[0];
+    let mut rest = &components[1..];
+    let mut paths = match rest.first() {
+        Some(os) if OS_NAMES.contains(os) => {
+            rest = &rest[1..];
+            vec![
+                vec![String::from("tasks"), format!("{}.{}", name, os)],
+                vec![String::from("tasks"), name.to_string(), os.to_string()],
+            ]
+        }
+        _ => vec![vec![String::from("tasks"), name.to_string()]],
+    };
+    while let Some(component) = rest.first() {
+        let indexes = rest[1..]
+            .iter()
+            .take_while(|index| index.parse::<usize>().is_ok())
+            .count();
+        if !CMDS_OPTIONS.contains(component) || indexes == 0 {
+            // A task called by name from the cmds of another, declared as a task of the file
+            return get_task_paths(&rest.join("."));
+        }
+        // A task given in the cmds of another, under the `task` key
+        for path in paths.iter_mut() {
+            path.extend(rest[..=indexes].iter().map(|c| c.to_string()));
+            path.push(String::from("task"));
+        }
+        rest = &rest[indexes + 1..];
+    }
+    paths
+}
+
+/// Returns where the task at the given path, or the field of it, is declared in the mom file.
+/// Returns None if it cannot be found, i.e. because the field is inherited from a base task.
+///
+/// # Arguments
+///
+/// * `source`: Content of the mom file
+/// * `task_name`: Name the task runs with, i.e. `build` or `build.cmds.2`
+/// * `field`: Path of the field inside the task, i.e. `cmds.0`, if any
+pub(crate) fn find_task_position(
+    source: &str,
+    task_name: &str,
+    field: Option<&str>,
+) -> Option<Position> {
+    get_task_paths(task_name).into_iter().find_map(|mut path| {
+        if let Some(field) = field {
+            path.extend(field.split('.').map(String::from));
+        }
+        find_yaml_path(source, &path)
+    })
+}
+
+/// Returns where the error of a task happened in the mom file it belongs to. Errors of
+/// subtasks are followed to the task that caused them. Only configuration errors, including
+/// the ones of templates, are located, as the other errors do not depend on the mom file.
+///
+/// # Arguments
+///
+/// * `source`: Content of the mom file
+/// * `error`: Error of the task
+pub(crate) fn find_error_position(source: &str, error: &AwareTaskError) -> Option<Position> {
+    let mut error = error;
+    while let TaskError::Subtask(ref inner) = error.error {
+        error = inner;
+    }
+    match error.error {
+        TaskError::Template { ref field, .. } => {
+            find_task_position(source, &error.task_name, Some(field))
+        }
+        TaskError::ConfigError(_) => find_task_position(source, &error.task_name, None),
+        _ => None,
+    }
+}
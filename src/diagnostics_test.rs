@@ -0,0 +1,169 @@
+use super::*;
+
+const SOURCE: &str = r#"
+version: 1
+
+tasks:
+  # Builds the project
+  build:
+    cmds:
+    - echo building
+    - cmd: echo {{ vars.target }}
+      retries: 2
+    - parallel:
+        - echo one
+        -
+          task:
+            cmds: [echo two]
+  "test.linux":
+    script: |
+      echo testing
+  test:
+    windows:
+      cmds:
+        - echo testing
+"#;
+
+fn to_path(path: &[&str]) -> Vec<String> {
+    path.iter().map(|c| c.to_string()).collect()
+}
+
+#[test]
+fn test_render_snippet() {
+    let snippet = render_snippet(
+        Path::new("mom.root.yml"),
+        SOURCE,
+        Position { line: 9, column: 7 },
+    );
+    assert_eq!(
+        snippet,
+        " --> mom.root.yml:9:7\n  |\n9 |     - cmd: echo {{ vars.target }}\n  |       ^"
+    );
+}
+
+#[test]
+fn test_render_snippet_at_position_0() {
+    let snippet = render_snippet(
+        Path::new("mom.root.yml"),
+        "version: 1",
+        Position { line: 0, column: 0 },
+    );
+    assert_eq!(snippet, " --> mom.root.yml:0:0\n  |\n0 | version: 1\n  | ^");
+}
+
+#[test]
+fn test_find_yaml_path() {
+    let cases: [(&[&str], usize, usize); 8] = [
+        (&["tasks"], 4, 1),
+        (&["tasks", "build"], 6, 3),
+        (&["tasks", "build", "cmds", "0"], 8, 7),
+        (&["tasks", "build", "cmds", "1", "retries"], 10, 7),
+        // Commands of parallel groups are under the `parallel` key
+        (&["tasks", "build", "cmds", "2", "1"], 13, 9),
+        (
+            &["tasks", "build", "cmds", "2", "1", "task", "cmds"],
+            15,
+            13,
+        ),
+        (&["tasks", "test.linux", "script"], 17, 5),
+        (&["tasks", "test", "windows", "cmds", "0"], 22, 11),
+    ];
+    for (path, line, column) in cases {
+        assert_eq!(
+            find_yaml_path(SOURCE, &to_path(path)),
+            Some(Position { line, column }),
+            "{:?}",
+            path
+        );
+    }
+    assert_eq!(find_yaml_path(SOURCE, &to_path(&["missing"])), None);
+    // Values in flow style are not followed, and no other position is given instead
+    assert_eq!(
+        find_yaml_path(
+            SOURCE,
+            &to_path(&["tasks", "build", "cmds", "2", "1", "task", "cmds", "0"])
+        ),
+        None
+    );
+    assert_eq!(
+        find_yaml_path(SOURCE, &to_path(&["tasks", "build", "script"])),
+        None
+    );
+}
+
+#[test]
+fn test_get_task_paths() {
+    assert_eq!(get_task_paths("build"), vec![to_path(&["tasks", "build"])]);
+    assert_eq!(
+        get_task_paths("build.linux"),
+        vec![
+            to_path(&["tasks", "build.linux"]),
+            to_path(&["tasks", "build", "linux"])
+        ]
+    );
+    assert_eq!(
+        get_task_paths("build.cmds.2.1.finally.0"),
+        vec![to_path(&[
+            "tasks", "build", "cmds", "2", "1", "task", "finally", "0", "task"
+        ])]
+    );
+    // Tasks called by name are declared in the tasks of the file
+    assert_eq!(
+        get_task_paths("build.cmds.2.test"),
+        vec![to_path(&["tasks", "test"])]
+    );
+    assert!(get_task_paths("backend:build").is_empty());
+}
+
+#[test]
+fn test_find_error_position() {
+    let error = AwareTaskError::new(
+        "build",
+        TaskError::Subtask(Box::new(AwareTaskError::new(
+            "build.cmds.2.1",
+            TaskError::ConfigError(String::from("Cannot set both `cmds` and `script`.")),
+        ))),
+    );
+    assert_eq!(
+        find_error_position(SOURCE, &error),
+        Some(Position {
+            line: 14,
+            column: 11
+        })
+    );
+
+    let error = AwareTaskError::new(
+        "test.windows",
+        TaskError::Template {
+            field: String::from("cmds.0"),
+            reason: String::from("Failed to render"),
+        },
+    );
+    assert_eq!(
+        find_error_position(SOURCE, &error),
+        Some(Position {
+            line: 22,
+            column: 11
+        })
+    );
+
+    // Errors that do not depend on the mom file are not located
+    let error = AwareTaskError::new(
+        "build",
+        TaskError::ProcessFailed {
+            code: Some(1),
+            signal: None,
+        },
+    );
+    assert_eq!(find_error_position(SOURCE, &error), None);
+}
+
+#[test]
+fn test_from_yaml_error() {
+    let source = "version: 1\ntasks: [\n";
+    let error = serde_yaml::from_str::<serde_yaml::Value>(source).unwrap_err();
+    let error = from_yaml_error(error, Path::new("mom.root.yml"), source);
+    let message = error.to_string();
+    assert!(!message.contains(" at line "));
+    assert!(message.contains(" --> mom.root.yml:3:1\n"));
+}
@@ -0,0 +1,298 @@
+#[cfg(test)]
+#[path = "dynamic_values_test.rs"]
+mod dynamic_values_test;
+
+use std::collections::{BTreeMap, HashMap};
+use std::env::current_dir;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::TaskError;
+use crate::run_context::RunContext;
+use crate::utils::{expand_arg, get_working_directory};
+
+/// Value computed from the output of a shell command, given as `{sh: "command"}` in `env` or
+/// `vars`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DynamicValue {
+    /// Command to run in the shell, `sh` in Unix and `cmd` in Windows
+    pub(crate) sh: String,
+}
+
+/// Value of an environment variable, given directly or computed from a shell command.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub(crate) enum EnvValue {
+    Value(String),
+    Dynamic(DynamicValue),
+}
+
+impl From<&str> for EnvValue {
+    fn from(value: &str) -> Self {
+        EnvValue::Value(String::from(value))
+    }
+}
+
+impl From<String> for EnvValue {
+    fn from(value: String) -> Self {
+        EnvValue::Value(value)
+    }
+}
+
+impl DynamicValue {
+    /// Returns the dynamic value a variable holds, that is, if it is a map with a `sh` string
+    /// as its only key.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: Value of the variable
+    pub(crate) fn from_var(value: &serde_yaml::Value) -> Option<DynamicValue> {
+        match value {
+            serde_yaml::Value::Mapping(map) if map.len() == 1 => match map.get("sh") {
+                Some(serde_yaml::Value::String(sh)) => Some(DynamicValue { sh: sh.clone() }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the value shown instead of the output when the command does not run, i.e. in
+    /// dry mode.
+    pub(crate) fn placeholder(&self) -> String {
+        format!("<sh: {}>", self.sh)
+    }
+
+    /// Runs the command and returns its trimmed output. The output is cached, so the same
+    /// command only runs once per invocation in the same working directory and env.
+    ///
+    /// # Arguments
+    ///
+    /// * `wd`: Working directory to run the command in
+    /// * `env`: Environment variables to run the command with
+    /// * `ctx`: Context of the current run, holding the cached values
+    pub(crate) fn compute(
+        &self,
+        wd: Option<&Path>,
+        env: &HashMap<String, String>,
+        ctx: &RunContext,
+    ) -> Result<String, TaskError> {
+        // Sorted, so that the same env always gives the same key
+        let sorted_env: BTreeMap<&String, &String> = env.iter().collect();
+        let key = format!(
+            "{}\n{}\n{:?}",
+            wd.unwrap_or(Path::new("")).display(),
+            self.sh,
+            sorted_env
+        );
+        ctx.cached_value(&key, || {
+            let mut command = if cfg!(windows) {
+                let mut command = Command::new("cmd");
+                command.arg("/C");
+                command
+            } else {
+                let mut command = Command::new("sh");
+                command.arg("-c");
+                command
+            };
+            command
+                .arg(&self.sh)
+                .envs(env)
+                .stdin(Stdio::null())
+                .stderr(Stdio::inherit());
+            if let Some(wd) = wd {
+                command.current_dir(wd);
+            }
+            let output = command
+                .output()
+                .map_err(|e| TaskError::RuntimeError(format!("`{}`: {}", self.sh, e)))?;
+            if !output.status.success() {
+                return Err(TaskError::RuntimeError(format!(
+                    "`{}` failed with {}",
+                    self.sh, output.status
+                )));
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+    }
+}
+
+/// Returns whether the template refers to the given name, i.e. as `vars.name`, `$NAME` or
+/// `get_env(name="NAME")`. It is a plain search, so it may find names that are not used, which
+/// only computes a value that is not needed.
+///
+/// # Arguments
+///
+/// * `template`: Template to search
+/// * `name`: Name of the env value or variable
+fn refers_to(template: &str, name: &str) -> bool {
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '_';
+    !name.is_empty()
+        && template.match_indices(name).any(|(i, _)| {
+            let before = template[..i].chars().next_back();
+            let after = template[i + name.len()..].chars().next();
+            !before.is_some_and(is_name_char) && !after.is_some_and(is_name_char)
+        })
+}
+
+/// Dynamic env and vars of a task, which are only computed once the task uses them.
+#[derive(Debug, Default)]
+pub(crate) struct DynamicValues {
+    /// Dynamic env values, by name
+    env: BTreeMap<String, DynamicValue>,
+    /// Dynamic variables, by name
+    vars: BTreeMap<String, DynamicValue>,
+    /// Working directory of the task, before expanding the env values it refers to
+    wd: Option<String>,
+    /// Folder of the mom file the task belongs to
+    directory: PathBuf,
+}
+
+impl DynamicValues {
+    /// Takes the dynamic values out of the env and vars of a task. Returns them along with the
+    /// env values given directly. Only the variables declared in a mom file can be dynamic, so
+    /// that the data of vars files and overrides never runs as a command.
+    ///
+    /// # Arguments
+    ///
+    /// * `env`: Environment variables of the task
+    /// * `vars`: Variables of the task, which only keep the values given directly
+    /// * `is_declared`: Returns whether the variable with the given name was declared in the
+    ///   `vars` of a task or mom file
+    /// * `wd`: Working directory of the task, if set
+    /// * `directory`: Folder of the mom file the task belongs to
+    pub(crate) fn take(
+        env: HashMap<String, EnvValue>,
+        vars: &mut HashMap<String, serde_yaml::Value>,
+        is_declared: impl Fn(&str) -> bool,
+        wd: Option<&str>,
+        directory: &Path,
+    ) -> (HashMap<String, String>, Self) {
+        let mut static_env = HashMap::new();
+        let mut dynamic = DynamicValues {
+            wd: wd.map(String::from),
+            directory: directory.to_path_buf(),
+            ..Default::default()
+        };
+        for (key, val) in env {
+            match val {
+                EnvValue::Value(val) => {
+                    static_env.insert(key, val);
+                }
+                EnvValue::Dynamic(val) => {
+                    dynamic.env.insert(key, val);
+                }
+            }
+        }
+        vars.retain(|key, val| match DynamicValue::from_var(val) {
+            Some(val) if is_declared(key) => {
+                dynamic.vars.insert(key.clone(), val);
+                false
+            }
+            _ => true,
+        });
+        (static_env, dynamic)
+    }
+
+    /// Returns whether any of the templates refers to a dynamic value.
+    pub(crate) fn are_used_by(&self, templates: &[&str]) -> bool {
+        let mut names = self.env.keys().chain(self.vars.keys());
+        names.any(|name| templates.iter().any(|t| refers_to(t, name)))
+    }
+
+    /// Computes the dynamic env, and the dynamic vars the templates refer to, adding them to
+    /// `env` and `vars` unless they are there already. The whole env is computed, as the
+    /// commands of the task get all of it.
+    ///
+    /// The env values `wd` refers to run in the folder of the mom file, as they decide the
+    /// working directory, and the rest in the working directory of the task, all of them with
+    /// the env values given directly. The vars run in the working directory with the whole env.
+    /// In dry mode, nothing runs, and the values are placeholders.
+    ///
+    /// # Arguments
+    ///
+    /// * `templates`: Templates of the task
+    /// * `env`: Environment variables of the task
+    /// * `vars`: Variables of the task
+    /// * `ctx`: Context of the current run, holding the cached values
+    pub(crate) fn resolve(
+        &self,
+        templates: &[&str],
+        env: &mut HashMap<String, String>,
+        vars: &mut HashMap<String, serde_yaml::Value>,
+        ctx: &RunContext,
+    ) -> Result<(), TaskError> {
+        let static_env: HashMap<String, String> = env
+            .iter()
+            .filter(|(key, _)| !self.env.contains_key(*key))
+            .map(|(key, val)| (key.clone(), val.clone()))
+            .collect();
+        let compute = |value: &DynamicValue, wd: &Path, env: &HashMap<String, String>| {
+            if ctx.dry_run {
+                Ok(value.placeholder())
+            } else {
+                value.compute(Some(wd), env, ctx)
+            }
+        };
+
+        let wd_template = self.wd.as_deref().unwrap_or_default();
+        let (wd_env, task_env): (Vec<_>, Vec<_>) = self
+            .env
+            .iter()
+            .filter(|(key, _)| !env.contains_key(*key))
+            .partition(|(key, _)| refers_to(wd_template, key));
+        for (key, value) in wd_env {
+            let value = compute(value, &self.directory, &static_env)
+                .map_err(|e| compute_error("env", key, e))?;
+            env.insert(key.clone(), value);
+        }
+        let wd = match &self.wd {
+            Some(wd) => get_working_directory(&self.directory, expand_arg(wd, env).as_ref()),
+            None => current_dir().unwrap_or_default(),
+        };
+        for (key, value) in task_env {
+            let value =
+                compute(value, &wd, &static_env).map_err(|e| compute_error("env", key, e))?;
+            env.insert(key.clone(), value);
+        }
+
+        for (key, value) in self.vars.iter() {
+            if vars.contains_key(key) || !templates.iter().any(|t| refers_to(t, key)) {
+                continue;
+            }
+            let value = compute(value, &wd, env).map_err(|e| compute_error("vars", key, e))?;
+            vars.insert(key.clone(), serde_yaml::Value::String(value));
+        }
+        Ok(())
+    }
+
+    /// Sets every dynamic value to its placeholder, without running any command.
+    ///
+    /// # Arguments
+    ///
+    /// * `env`: Environment variables of the task
+    /// * `vars`: Variables of the task
+    pub(crate) fn set_placeholders(
+        &self,
+        env: &mut HashMap<String, String>,
+        vars: &mut HashMap<String, serde_yaml::Value>,
+    ) {
+        for (key, value) in self.env.iter() {
+            env.insert(key.clone(), value.placeholder());
+        }
+        for (key, value) in self.vars.iter() {
+            vars.insert(key.clone(), serde_yaml::Value::String(value.placeholder()));
+        }
+    }
+}
+
+/// Returns the error of a dynamic value that could not be computed.
+fn compute_error(kind: &str, key: &str, error: TaskError) -> TaskError {
+    let reason = match error {
+        TaskError::RuntimeError(reason) => reason,
+        error => error.to_string(),
+    };
+    TaskError::RuntimeError(format!("Could not compute `{}.{}`: {}", kind, key, reason))
+}
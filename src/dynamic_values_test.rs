@@ -0,0 +1,203 @@
+use super::*;
+use assert_fs::TempDir;
+use std::fs;
+
+#[test]
+fn test_deserialize_env_value() {
+    let env: HashMap<String, EnvValue> = serde_yaml::from_str(
+        r#"
+        STATIC: "value"
+        DYNAMIC: {sh: "echo value"}
+    "#,
+    )
+    .unwrap();
+    assert_eq!(env.get("STATIC").unwrap(), &EnvValue::from("value"));
+    assert_eq!(
+        env.get("DYNAMIC").unwrap(),
+        &EnvValue::Dynamic(DynamicValue {
+            sh: String::from("echo value")
+        })
+    );
+
+    let err = serde_yaml::from_str::<HashMap<String, EnvValue>>("KEY: {cmd: \"echo\"}");
+    assert!(err.is_err());
+}
+
+#[test]
+fn test_dynamic_value_from_var() {
+    let value: serde_yaml::Value = serde_yaml::from_str("{sh: \"echo value\"}").unwrap();
+    assert_eq!(
+        DynamicValue::from_var(&value),
+        Some(DynamicValue {
+            sh: String::from("echo value")
+        })
+    );
+
+    for value in ["value", "{sh: 1}", "{sh: \"echo\", other: 1}", "[1, 2]"] {
+        let value: serde_yaml::Value = serde_yaml::from_str(value).unwrap();
+        assert_eq!(DynamicValue::from_var(&value), None);
+    }
+}
+
+#[test]
+fn test_refers_to() {
+    assert!(refers_to("{{ vars.version }}", "version"));
+    assert!(refers_to("echo $VERSION", "VERSION"));
+    assert!(refers_to("{{ get_env(name=\"VERSION\") }}", "VERSION"));
+    assert!(!refers_to("{{ vars.version_file }}", "version"));
+    assert!(!refers_to("echo $APP_VERSION", "VERSION"));
+    assert!(!refers_to("echo", ""));
+}
+
+#[test]
+fn test_take_dynamic_values() {
+    let env: HashMap<String, EnvValue> = serde_yaml::from_str(
+        r#"
+        STATIC: "value"
+        DYNAMIC: {sh: "echo value"}
+    "#,
+    )
+    .unwrap();
+    let mut vars: HashMap<String, serde_yaml::Value> = serde_yaml::from_str(
+        r#"
+        count: 1
+        dynamic: {sh: "echo value"}
+    "#,
+    )
+    .unwrap();
+    let (env, dynamic) = DynamicValues::take(env, &mut vars, |_| true, None, Path::new(""));
+    assert_eq!(
+        env,
+        HashMap::from([(String::from("STATIC"), String::from("value"))])
+    );
+    assert_eq!(vars.len(), 1);
+    assert_eq!(vars.get("count").unwrap(), &serde_yaml::Value::from(1));
+    assert!(dynamic.are_used_by(&["{{ vars.dynamic }}"]));
+    assert!(dynamic.are_used_by(&["echo $DYNAMIC"]));
+    assert!(!dynamic.are_used_by(&["echo $STATIC {{ vars.count }}"]));
+}
+
+#[test]
+fn test_take_undeclared_vars_as_literals() {
+    let mut vars: HashMap<String, serde_yaml::Value> = serde_yaml::from_str(
+        r#"
+        declared: {sh: "echo value"}
+        loaded: {sh: "echo value"}
+    "#,
+    )
+    .unwrap();
+    let (_, dynamic) = DynamicValues::take(
+        HashMap::new(),
+        &mut vars,
+        |key| key == "declared",
+        None,
+        Path::new(""),
+    );
+    assert!(dynamic.are_used_by(&["{{ vars.declared }}"]));
+    assert!(!dynamic.are_used_by(&["{{ vars.loaded }}"]));
+    assert!(!vars.contains_key("declared"));
+    assert_eq!(
+        vars.get("loaded").unwrap(),
+        &serde_yaml::from_str::<serde_yaml::Value>("{sh: \"echo value\"}").unwrap()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_resolve_env_and_vars() {
+    let ctx = RunContext::new(false);
+    let env: HashMap<String, EnvValue> = serde_yaml::from_str(
+        r#"
+        NAME: "mom"
+        GREETING: {sh: "echo hello $NAME"}
+    "#,
+    )
+    .unwrap();
+    let mut vars: HashMap<String, serde_yaml::Value> = serde_yaml::from_str(
+        r#"
+        count: 1
+        greeting: {sh: "echo $GREETING"}
+        unused: {sh: "exit 1"}
+    "#,
+    )
+    .unwrap();
+    let (mut env, dynamic) = DynamicValues::take(env, &mut vars, |_| true, None, Path::new(""));
+    dynamic
+        .resolve(&["{{ vars.greeting }}"], &mut env, &mut vars, &ctx)
+        .unwrap();
+    assert_eq!(env.get("NAME").unwrap(), "mom");
+    assert_eq!(env.get("GREETING").unwrap(), "hello mom");
+    assert_eq!(vars.get("count").unwrap(), &serde_yaml::Value::from(1));
+    assert_eq!(
+        vars.get("greeting").unwrap(),
+        &serde_yaml::Value::from("hello mom")
+    );
+    // Not used by the templates, so it does not run
+    assert!(!vars.contains_key("unused"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_resolve_in_working_directory() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::create_dir(tmp_dir.join("sub")).unwrap();
+    let ctx = RunContext::new(false);
+    let env: HashMap<String, EnvValue> = serde_yaml::from_str(
+        r#"
+        SUB_DIR: {sh: "echo sub"}
+        HERE: {sh: "basename \"$(pwd)\""}
+    "#,
+    )
+    .unwrap();
+    let mut vars = HashMap::new();
+    let (mut env, dynamic) =
+        DynamicValues::take(env, &mut vars, |_| true, Some("$SUB_DIR"), tmp_dir.path());
+    dynamic.resolve(&[], &mut env, &mut vars, &ctx).unwrap();
+    // The ones the working directory refers to run in the folder of the mom file
+    assert_eq!(env.get("SUB_DIR").unwrap(), "sub");
+    assert_eq!(env.get("HERE").unwrap(), "sub");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_compute_cached_by_env() {
+    let ctx = RunContext::new(false);
+    let value = DynamicValue {
+        sh: String::from("echo $NAME"),
+    };
+    let env = HashMap::from([(String::from("NAME"), String::from("first"))]);
+    assert_eq!(value.compute(None, &env, &ctx).unwrap(), "first");
+    let env = HashMap::from([(String::from("NAME"), String::from("second"))]);
+    assert_eq!(value.compute(None, &env, &ctx).unwrap(), "second");
+}
+
+#[test]
+fn test_resolve_dry_run() {
+    let ctx = RunContext::new(true);
+    let env: HashMap<String, EnvValue> = serde_yaml::from_str("BROKEN: {sh: \"exit 3\"}").unwrap();
+    let mut vars: HashMap<String, serde_yaml::Value> =
+        serde_yaml::from_str("broken: {sh: \"exit 3\"}").unwrap();
+    let (mut env, dynamic) = DynamicValues::take(env, &mut vars, |_| true, None, Path::new(""));
+    dynamic
+        .resolve(&["{{ vars.broken }}"], &mut env, &mut vars, &ctx)
+        .unwrap();
+    assert_eq!(env.get("BROKEN").unwrap(), "<sh: exit 3>");
+    assert_eq!(
+        vars.get("broken").unwrap(),
+        &serde_yaml::Value::from("<sh: exit 3>")
+    );
+}
+
+#[test]
+fn test_resolve_failing_value() {
+    let ctx = RunContext::new(false);
+    let env: HashMap<String, EnvValue> = serde_yaml::from_str("BROKEN: {sh: \"exit 3\"}").unwrap();
+    let (mut env, dynamic) =
+        DynamicValues::take(env, &mut HashMap::new(), |_| true, None, Path::new(""));
+    let err = dynamic
+        .resolve(&[], &mut env, &mut HashMap::new(), &ctx)
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Could not compute `env.BROKEN`: `exit 3` failed with"));
+}
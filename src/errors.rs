@@ -1,29 +1,130 @@
 #[cfg(test)]
 #[path = "errors_test.rs"]
+#[allow(unknown_lints, clippy::io_other_error)] // Only known by newer versions of clippy
 mod errors_test;
 
 use std::error::Error;
-use std::fmt;
+use std::path::PathBuf;
+use std::{fmt, fs};
+
+use colored::Colorize;
+
+use crate::command_options::DurationValue;
+use crate::diagnostics::{find_error_position, render_snippet};
 
 /// Represents an error that can occur in a task
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum TaskError {
     /// Raised when there is an error running a task
     RuntimeError(String),
     /// Raised when the task is improperly configured
     ConfigError(String),
+    /// Raised when a template cannot be compiled or rendered. The field is the path of the option
+    /// the template is given in, i.e. `cmds.0`, used to point to it in the mom file
+    Template {
+        field: String,
+        reason: String,
+    },
+    /// Raised when the arguments do not match the parameters of the task
+    InvalidArgs(String),
+    /// Raised when a task or command, given by name, does not finish before its timeout
+    Timeout(String, DurationValue),
+    /// Raised when a process exits with a code that is not successful, or is terminated by a
+    /// signal, on Unix
+    ProcessFailed {
+        code: Option<i32>,
+        signal: Option<i32>,
+    },
+    /// Raised when a task run by another task fails
+    Subtask(Box<AwareTaskError>),
+    /// Raised when several commands that run concurrently fail, in the order they were given
+    Parallel(Vec<TaskError>),
     NotFound(String),
 }
 
+impl TaskError {
+    /// Returns the exit code mom should exit with because of this error, if it was caused by a
+    /// process that failed. Processes terminated by a signal result in 128 plus the signal
+    /// number, like in most shells.
+    pub(crate) fn exit_code(&self) -> Option<i32> {
+        match *self {
+            TaskError::ProcessFailed {
+                code: Some(code), ..
+            } => Some(code),
+            TaskError::ProcessFailed {
+                signal: Some(signal),
+                ..
+            } => Some(128 + signal),
+            TaskError::Subtask(ref error) => error.error.exit_code(),
+            // The first command that failed with an exit code decides it
+            TaskError::Parallel(ref errors) => errors.iter().find_map(|e| e.exit_code()),
+            _ => None,
+        }
+    }
+
+    /// Returns the reason of the error, without the kind of the error, i.e.
+    /// `Improperly configured`.
+    pub(crate) fn reason(&self) -> String {
+        match self {
+            TaskError::RuntimeError(reason)
+            | TaskError::ConfigError(reason)
+            | TaskError::Template { reason, .. }
+            | TaskError::InvalidArgs(reason) => reason.clone(),
+            TaskError::Parallel(errors) => get_parallel_reason(errors),
+            error => error.to_string(),
+        }
+    }
+
+    /// Returns the error of a template that cannot be compiled or rendered.
+    ///
+    /// # Arguments
+    ///
+    /// * `field`: Path of the option the template is given in, i.e. `cmds.0`
+    /// * `err`: Error returned by Tera
+    pub(crate) fn template(field: &str, err: tera::Error) -> TaskError {
+        TaskError::Template {
+            field: String::from(field),
+            reason: get_tera_error_reason(&err),
+        }
+    }
+}
+
 impl fmt::Display for TaskError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             TaskError::RuntimeError(ref reason) => {
                 write!(f, "Runtime error:\n{}", reason)
             }
-            TaskError::ConfigError(ref reason) => {
+            TaskError::ConfigError(ref reason) | TaskError::Template { ref reason, .. } => {
                 write!(f, "Improperly configured:\n{}", reason)
             }
+            TaskError::InvalidArgs(ref reason) => {
+                write!(f, "Invalid arguments:\n{}", reason)
+            }
+            TaskError::Timeout(ref name, ref timeout) => {
+                write!(
+                    f,
+                    "Timed out:\n`{}` did not finish within {}.",
+                    name, timeout
+                )
+            }
+            TaskError::ProcessFailed { code, signal } => match (code, signal) {
+                (Some(code), _) => write!(
+                    f,
+                    "Runtime error:\nProcess terminated with exit code {}",
+                    code
+                ),
+                (None, Some(signal)) => {
+                    write!(f, "Runtime error:\nProcess terminated by signal {}", signal)
+                }
+                (None, None) => write!(f, "Runtime error:\nProcess did not terminate correctly"),
+            },
+            TaskError::Subtask(ref error) => {
+                write!(f, "Runtime error:\n{}", error)
+            }
+            TaskError::Parallel(ref errors) => {
+                write!(f, "Runtime error:\n{}", get_parallel_reason(errors))
+            }
             TaskError::NotFound(ref name) => {
                 write!(f, "Task `{}` not found.", name)
             }
@@ -31,17 +132,36 @@ impl fmt::Display for TaskError {
     }
 }
 
-impl Error for TaskError {}
+impl Error for TaskError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            TaskError::Subtask(ref error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the number of commands that failed concurrently, followed by their errors.
+fn get_parallel_reason(errors: &[TaskError]) -> String {
+    let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    format!("{} commands failed:\n{}", errors.len(), errors.join("\n"))
+}
+
+/// Returns the message of a Tera error, followed by the errors that caused it, as the message
+/// alone does not say what is wrong with the template.
+fn get_tera_error_reason(err: &tera::Error) -> String {
+    let mut full_error = err.to_string();
+    let mut source = err.source();
+    while let Some(inner) = source {
+        full_error.push_str(&format!("\nCaused by: {}", inner));
+        source = inner.source();
+    }
+    full_error
+}
 
 impl From<tera::Error> for TaskError {
     fn from(err: tera::Error) -> TaskError {
-        let mut full_error = err.to_string();
-        let mut source = err.source();
-        while let Some(inner) = source {
-            full_error.push_str(&format!("\nCaused by: {}", inner));
-            source = inner.source();
-        }
-        TaskError::ConfigError(full_error)
+        TaskError::ConfigError(get_tera_error_reason(&err))
     }
 }
 
@@ -52,15 +172,14 @@ impl From<std::io::Error> for TaskError {
 }
 
 // We convert back to TaskError in case a subtask fails
-// TODO: Use source() instead
 impl From<AwareTaskError> for TaskError {
     fn from(err: AwareTaskError) -> TaskError {
-        TaskError::RuntimeError(err.to_string())
+        TaskError::Subtask(Box::new(err))
     }
 }
 
 /// Task error aware of the task name
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct AwareTaskError {
     /// Name of the task that failed
     pub(crate) task_name: String,
@@ -92,3 +211,133 @@ impl AwareTaskError {
         }
     }
 }
+
+/// Error of a task that refers to a task that does not exist, i.e. in `extend`, `deps` or `cmds`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MissingTaskError {
+    /// Name of the task that refers to the missing one
+    pub(crate) task_name: String,
+    /// Name of the task that does not exist
+    pub(crate) missing_name: String,
+}
+
+impl MissingTaskError {
+    /// Creates a new MissingTaskError
+    ///
+    /// # Arguments
+    ///
+    /// * `task_name`: Name of the task that refers to the missing one
+    /// * `missing_name`: Name of the task that does not exist
+    pub(crate) fn new(task_name: &str, missing_name: &str) -> MissingTaskError {
+        MissingTaskError {
+            task_name: String::from(task_name),
+            missing_name: String::from(missing_name),
+        }
+    }
+}
+
+impl fmt::Display for MissingTaskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Task {} cannot inherit from non-existing task {}.",
+            self.task_name, self.missing_name
+        )
+    }
+}
+
+impl Error for MissingTaskError {}
+
+/// Error reading an env or vars file of a mom file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ReadFileError {
+    /// Path of the file that cannot be read
+    pub(crate) path: PathBuf,
+    /// Description of the error, including the path
+    pub(crate) message: String,
+}
+
+impl fmt::Display for ReadFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for ReadFileError {}
+
+/// Error of a task run from the command line, aware of the file the task is declared in
+#[derive(Debug)]
+pub(crate) struct FileTaskError {
+    /// Path of the mom file the task is declared in
+    pub(crate) path: PathBuf,
+    /// The error that caused the task to fail
+    pub(crate) error: AwareTaskError,
+    /// Snippet of the mom file pointing to where the error happened, if it can be located
+    pub(crate) snippet: Option<String>,
+}
+
+impl FileTaskError {
+    /// Creates a new FileTaskError, pointing to where the error happened in the mom file if it
+    /// is caused by its configuration, i.e. an invalid template in `cmds`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path of the mom file the task is declared in
+    /// * `error`: The error that caused the task to fail
+    pub(crate) fn new(path: PathBuf, error: AwareTaskError) -> FileTaskError {
+        let snippet = fs::read_to_string(&path).ok().and_then(|source| {
+            let position = find_error_position(&source, &error)?;
+            Some(render_snippet(&path, &source, position))
+        });
+        FileTaskError {
+            path,
+            error,
+            snippet,
+        }
+    }
+}
+
+impl fmt::Display for FileTaskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:\n{}", self.path.to_string_lossy().red(), self.error)?;
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{}", snippet)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for FileTaskError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Error of running several tasks from the command line with `--keep-going`, where some of them
+/// failed. The errors of the tasks are reported as they happen.
+#[derive(Debug)]
+pub(crate) struct TasksFailedError {
+    /// Names of the tasks that failed, in the order they ran
+    pub(crate) failed: Vec<String>,
+    /// Exit code of the first task that failed
+    pub(crate) exit_code: i32,
+}
+
+impl fmt::Display for TasksFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let task_word = if self.failed.len() == 1 {
+            "task"
+        } else {
+            "tasks"
+        };
+        write!(
+            f,
+            "{} {} failed: {}",
+            self.failed.len(),
+            task_word,
+            self.failed.join(", ")
+        )
+    }
+}
+
+impl Error for TasksFailedError {}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::command_options::DurationValue;
 
 #[test]
 fn test_from_err_to_task_error() {
@@ -34,13 +35,65 @@ fn test_from_tera_err_wit_cause_to_task_error() {
     assert_eq!(task_err.to_string(), "Improperly configured:\nIo error while writing rendered value to output: Other\nCaused by: test");
 }
 
+#[test]
+fn test_template_err() {
+    let err = tera::Error::from(std::io::Error::other("test"));
+    let task_err = TaskError::template("cmds.0", err);
+    let expected = TaskError::Template {
+        field: String::from("cmds.0"),
+        reason: String::from(
+            "Io error while writing rendered value to output: Other\nCaused by: test",
+        ),
+    };
+    assert_eq!(task_err, expected);
+
+    // Shown like any other configuration error
+    assert_eq!(task_err.to_string(), "Improperly configured:\nIo error while writing rendered value to output: Other\nCaused by: test");
+}
+
+#[test]
+fn test_file_task_err() {
+    let tmp_dir = assert_fs::TempDir::new().unwrap();
+    let path = tmp_dir.path().join("mom.root.yml");
+    fs::write(
+        &path,
+        "version: 1\ntasks:\n  build:\n    cmds:\n      - echo {{ 1 }\n",
+    )
+    .unwrap();
+
+    let error = AwareTaskError::new(
+        "build",
+        TaskError::Template {
+            field: String::from("cmds.0"),
+            reason: String::from("Failed to parse 'tasks.build.cmds.0'"),
+        },
+    );
+    let err = FileTaskError::new(path.clone(), error.clone());
+    assert_eq!(
+        err.snippet,
+        Some(format!(
+            " --> {}:5:9\n  |\n5 |       - echo {{{{ 1 }}\n  |         ^",
+            path.display()
+        ))
+    );
+    assert!(err.to_string().ends_with(err.snippet.as_ref().unwrap()));
+
+    // Errors that do not depend on the mom file are not located
+    let error = AwareTaskError::new(
+        "build",
+        TaskError::ProcessFailed {
+            code: Some(1),
+            signal: None,
+        },
+    );
+    assert_eq!(FileTaskError::new(path, error).snippet, None);
+}
+
 #[test]
 fn test_from_aware_task_error_to_task_error() {
     let err = AwareTaskError::new("test", TaskError::ConfigError(String::from("test")));
-    let task_err: TaskError = err.into();
-    let expected = TaskError::RuntimeError(String::from(
-        "Task `test` failed:\nImproperly configured:\ntest",
-    ));
+    let task_err: TaskError = err.clone().into();
+    let expected = TaskError::Subtask(Box::new(err));
     assert_eq!(task_err, expected);
 
     assert_eq!(
@@ -54,3 +107,102 @@ fn test_not_found_task_err() {
     let err = TaskError::NotFound(String::from("test"));
     assert_eq!(err.to_string(), "Task `test` not found.");
 }
+
+#[test]
+fn test_timeout_err() {
+    let err = TaskError::Timeout(
+        String::from("test.cmds.0"),
+        DurationValue(std::time::Duration::from_secs(30)),
+    );
+    assert_eq!(
+        err.to_string(),
+        "Timed out:\n`test.cmds.0` did not finish within 30s."
+    );
+}
+
+#[test]
+fn test_process_failed_err() {
+    let err = TaskError::ProcessFailed {
+        code: Some(3),
+        signal: None,
+    };
+    assert_eq!(
+        err.to_string(),
+        "Runtime error:\nProcess terminated with exit code 3"
+    );
+    assert_eq!(err.exit_code(), Some(3));
+
+    let err = TaskError::ProcessFailed {
+        code: None,
+        signal: Some(15),
+    };
+    assert_eq!(
+        err.to_string(),
+        "Runtime error:\nProcess terminated by signal 15"
+    );
+    assert_eq!(err.exit_code(), Some(143));
+
+    let err = TaskError::ProcessFailed {
+        code: None,
+        signal: None,
+    };
+    assert_eq!(
+        err.to_string(),
+        "Runtime error:\nProcess did not terminate correctly"
+    );
+    assert_eq!(err.exit_code(), None);
+
+    // The exit code is kept through subtasks
+    let err: TaskError = AwareTaskError::new(
+        "sub",
+        TaskError::ProcessFailed {
+            code: Some(3),
+            signal: None,
+        },
+    )
+    .into();
+    assert_eq!(err.exit_code(), Some(3));
+    assert_eq!(TaskError::NotFound(String::from("test")).exit_code(), None);
+}
+
+#[test]
+fn test_parallel_err() {
+    let err = TaskError::Parallel(vec![
+        TaskError::RuntimeError(String::from("test")),
+        TaskError::ProcessFailed {
+            code: Some(3),
+            signal: None,
+        },
+        TaskError::ProcessFailed {
+            code: Some(4),
+            signal: None,
+        },
+    ]);
+    assert_eq!(
+        err.to_string(),
+        "Runtime error:\n3 commands failed:\nRuntime error:\ntest\n\
+         Runtime error:\nProcess terminated with exit code 3\n\
+         Runtime error:\nProcess terminated with exit code 4"
+    );
+    // The first command that failed with an exit code decides it
+    assert_eq!(err.exit_code(), Some(3));
+    assert_eq!(
+        TaskError::Parallel(vec![TaskError::RuntimeError(String::from("test"))]).exit_code(),
+        None
+    );
+}
+
+#[test]
+fn test_tasks_failed_err() {
+    let err = TasksFailedError {
+        failed: vec![String::from("lint"), String::from("test")],
+        exit_code: 3,
+    };
+    assert_eq!(err.to_string(), "2 tasks failed: lint, test");
+
+    let err = TasksFailedError {
+        failed: vec![String::from("lint")],
+        exit_code: 1,
+    };
+    assert_eq!(err.to_string(), "1 task failed: lint");
+}
@@ -0,0 +1,452 @@
+#[cfg(test)]
+#[path = "explain_test.rs"]
+mod explain_test;
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::errors::TaskError;
+use crate::mom_files::MomFile;
+use crate::run_context::RunContext;
+use crate::serde_common::Origin;
+use crate::tasks::{Cmd, ResolvedTask, Task, TaskBody};
+use crate::utils::expand_arg;
+
+/// Indentation added for each nested level of the explanation
+const INDENT: &str = "  ";
+
+/// Value of the env or vars of a task, along with where it comes from
+#[derive(Debug)]
+pub(crate) struct ExplainedValue {
+    pub(crate) key: String,
+    /// Value of an env variable, or a variable serialized as JSON
+    pub(crate) value: String,
+    pub(crate) origin: Origin,
+}
+
+/// Parameter of a task, with the value it takes when it is not given
+#[derive(Debug)]
+pub(crate) struct ExplainedParam {
+    pub(crate) name: String,
+    /// Value serialized as JSON, or None if the parameter is required
+    pub(crate) value: Option<String>,
+}
+
+/// Entry of the `cmds` or `finally` of a task, as it would run
+#[derive(Debug)]
+pub(crate) enum ExplainedCmd {
+    /// Command rendered with the context of the task, or the reason it cannot be rendered
+    Cmd {
+        path: String,
+        cmd: Result<String, String>,
+    },
+    /// Task run from the commands, or the reason it cannot run
+    Task {
+        path: String,
+        task: Result<Box<TaskExplanation>, String>,
+    },
+    /// Commands that run concurrently
+    Parallel {
+        path: String,
+        cmds: Vec<ExplainedCmd>,
+    },
+}
+
+/// What a task runs, with its templates rendered
+#[derive(Debug)]
+pub(crate) enum ExplainedRun {
+    /// Script and the rendered runner it runs with
+    Script {
+        script_runner: Result<String, String>,
+        script: Result<String, String>,
+    },
+    /// Program and its rendered arguments
+    Program {
+        program: String,
+        args: Result<String, String>,
+    },
+    /// Commands, which run concurrently if `parallel` is set
+    Cmds {
+        parallel: bool,
+        cmds: Vec<ExplainedCmd>,
+    },
+    /// The task does not set anything to run
+    Nothing,
+}
+
+/// Task as it would run, after extending it from its bases, loading its dotenv and vars files,
+/// merging it with the task that calls it and the mom file it belongs to, and selecting the
+/// version for the current OS.
+#[derive(Debug)]
+pub(crate) struct TaskExplanation {
+    /// Name the task runs with, i.e. `build.linux` or `build.cmds.2`
+    pub(crate) name: String,
+    /// Path of the mom file the task belongs to
+    pub(crate) path: PathBuf,
+    /// Tasks it extends from
+    pub(crate) extend: Vec<String>,
+    /// Working directory the commands run in
+    pub(crate) wd: Option<PathBuf>,
+    /// Env of the task, sorted by key
+    pub(crate) env: Vec<ExplainedValue>,
+    /// Variables of the task, sorted by key
+    pub(crate) vars: Vec<ExplainedValue>,
+    /// Parameters of the task, in the order they are declared
+    pub(crate) params: Vec<ExplainedParam>,
+    /// Names of the templates that can be included
+    pub(crate) incl: Vec<String>,
+    pub(crate) run: ExplainedRun,
+    pub(crate) finally: Vec<ExplainedCmd>,
+}
+
+impl ExplainedCmd {
+    /// Returns a single entry of the cmds option of the task as it would run. The output of
+    /// the commands that register it is replaced with a placeholder.
+    ///
+    /// # Arguments
+    ///
+    /// * `task`: Task the command belongs to
+    /// * `cmd`: Command to explain
+    /// * `path`: Option and index of the command, i.e. `cmds.2`
+    /// * `mom_file`: Mom file the task belongs to
+    /// * `resolved`: Task resolved to render its templates
+    /// * `ctx`: Context of the current run, holding the overrides
+    fn new(
+        task: &Task,
+        cmd: &Cmd,
+        path: String,
+        mom_file: &MomFile,
+        resolved: &mut ResolvedTask,
+        ctx: &RunContext,
+    ) -> Self {
+        let render = |resolved: &mut ResolvedTask, cmd: &str| {
+            resolved
+                .render(task.get_name(), &path, cmd)
+                .map_err(|e| e.reason())
+        };
+        match cmd {
+            Cmd::Cmd(cmd) => ExplainedCmd::Cmd {
+                cmd: render(resolved, cmd),
+                path,
+            },
+            Cmd::Inline(inline) => {
+                let cmd = render(resolved, &inline.cmd);
+                if let Some(register) = &inline.register {
+                    resolved.register_output(register, &format!("<output of {}>", path));
+                }
+                ExplainedCmd::Cmd { path, cmd }
+            }
+            Cmd::TaskName(task_name) => {
+                let task = task
+                    .get_cmds_task_by_name(task_name, &path, mom_file)
+                    .and_then(|(mom_file, task)| TaskExplanation::new(&task, mom_file, ctx));
+                ExplainedCmd::Task {
+                    path,
+                    task: task.map(Box::new).map_err(|e| e.reason()),
+                }
+            }
+            Cmd::Task(cmd_task) => {
+                let task = task
+                    .get_cmds_task(cmd_task, &path, mom_file)
+                    .and_then(|task| TaskExplanation::new(&task, mom_file, ctx));
+                ExplainedCmd::Task {
+                    path,
+                    task: task.map(Box::new).map_err(|e| e.reason()),
+                }
+            }
+            // Like when running them, each command gets its own copy of the resolved task
+            Cmd::Parallel(cmds) => ExplainedCmd::Parallel {
+                cmds: cmds
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cmd)| {
+                        let cmd_path = format!("{path}.{i}");
+                        ExplainedCmd::new(task, cmd, cmd_path, mom_file, &mut resolved.clone(), ctx)
+                    })
+                    .collect(),
+                path,
+            },
+        }
+    }
+
+    /// Returns the given commands of the task as they would run, one after the other.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix`: Path the index of each command is appended to, i.e. `cmds`
+    fn from_cmds(
+        task: &Task,
+        cmds: &[Cmd],
+        prefix: &str,
+        mom_file: &MomFile,
+        resolved: &mut ResolvedTask,
+        ctx: &RunContext,
+    ) -> Vec<Self> {
+        cmds.iter()
+            .enumerate()
+            .map(|(i, cmd)| {
+                let cmd_path = format!("{prefix}.{i}");
+                ExplainedCmd::new(task, cmd, cmd_path, mom_file, resolved, ctx)
+            })
+            .collect()
+    }
+}
+
+impl TaskExplanation {
+    /// Returns the task as it would run, with its env and vars merged from every place they
+    /// can be given, along with where each value comes from, and its templates rendered,
+    /// including the ones of the tasks it runs from its commands. Nothing runs, so the dynamic
+    /// env and vars are shown as placeholders. Parameters without a default cannot be given, so
+    /// the templates using them cannot be rendered.
+    ///
+    /// # Arguments
+    ///
+    /// * `task`: Task to explain
+    /// * `mom_file`: Mom file the task belongs to
+    /// * `ctx`: Context of the current run, holding the overrides
+    pub(crate) fn new(
+        task: &Task,
+        mom_file: &MomFile,
+        ctx: &RunContext,
+    ) -> Result<Self, TaskError> {
+        let mut resolved = task.resolve_without_running(mom_file, ctx)?;
+
+        let params = task
+            .get_params()
+            .iter()
+            .map(|param| ExplainedParam {
+                name: param.name.clone(),
+                value: resolved
+                    .params
+                    .get(&param.name)
+                    .map(|value| serde_json::to_string(value).unwrap_or_default()),
+            })
+            .collect();
+        let mut env: Vec<ExplainedValue> = resolved
+            .env
+            .iter()
+            .map(|(key, value)| ExplainedValue {
+                key: key.clone(),
+                value: value.clone(),
+                origin: task.get_env_origin(key, mom_file, ctx),
+            })
+            .collect();
+        env.sort_by(|a, b| a.key.cmp(&b.key));
+        let mut vars: Vec<ExplainedValue> = resolved
+            .vars
+            .iter()
+            .map(|(key, value)| ExplainedValue {
+                key: key.clone(),
+                value: serde_json::to_string(value).unwrap_or_default(),
+                origin: task.get_vars_origin(key, mom_file, ctx),
+            })
+            .collect();
+        vars.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let incl: BTreeSet<String> = mom_file
+            .common
+            .incl
+            .keys()
+            .chain(task.common.incl.keys())
+            .cloned()
+            .collect();
+
+        let name = task.get_name();
+        let run = match task.get_body() {
+            TaskBody::Script {
+                script,
+                script_runner,
+            } => {
+                // The script is only written to a file when the task runs
+                resolved.insert("script_path", "<script_path>");
+                ExplainedRun::Script {
+                    script: resolved
+                        .render(name, "script", script)
+                        .map_err(|e| e.reason()),
+                    script_runner: resolved
+                        .render(name, "script_runner", script_runner)
+                        .map_err(|e| e.reason()),
+                }
+            }
+            TaskBody::Program { program, args } => ExplainedRun::Program {
+                program: expand_arg(program, &resolved.env).to_string(),
+                args: resolved.render(name, "args", args).map_err(|e| e.reason()),
+            },
+            TaskBody::Cmds { cmds, parallel } => ExplainedRun::Cmds {
+                parallel,
+                cmds: ExplainedCmd::from_cmds(task, cmds, "cmds", mom_file, &mut resolved, ctx),
+            },
+            TaskBody::Nothing => ExplainedRun::Nothing,
+        };
+        let finally = ExplainedCmd::from_cmds(
+            task,
+            task.get_finally(),
+            "finally",
+            mom_file,
+            &mut resolved,
+            ctx,
+        );
+
+        Ok(TaskExplanation {
+            name: String::from(name),
+            path: mom_file.filepath.clone(),
+            extend: task.common.extend.iter().map(String::from).collect(),
+            wd: task.get_wd(mom_file, &resolved.env),
+            env,
+            vars,
+            params,
+            incl: incl.into_iter().collect(),
+            run,
+            finally,
+        })
+    }
+}
+
+/// Appends the given text to the lines, indenting its continuation lines
+///
+/// # Arguments
+///
+/// * `lines`: Lines to append to
+/// * `indent`: Indentation of the text
+/// * `text`: Text to append, i.e. `cmds.0: echo hello`
+fn push_text(lines: &mut Vec<String>, indent: &str, text: &str) {
+    let mut text_lines = text.lines();
+    lines.push(format!(
+        "{}{}",
+        indent,
+        text_lines.next().unwrap_or_default()
+    ));
+    for line in text_lines {
+        lines.push(format!("{}{}{}", indent, INDENT, line));
+    }
+}
+
+/// Appends the rendered template, or the reason it cannot be rendered, to the lines
+fn push_rendered(
+    lines: &mut Vec<String>,
+    indent: &str,
+    name: &str,
+    rendered: &Result<String, String>,
+) {
+    match rendered {
+        Ok(rendered) => push_text(lines, indent, &format!("{}: {}", name, rendered)),
+        Err(reason) => push_text(
+            lines,
+            indent,
+            &format!("{}: cannot render: {}", name, reason),
+        ),
+    }
+}
+
+/// Appends the env or vars, with the origin of each value, to the lines
+fn push_values(lines: &mut Vec<String>, indent: &str, name: &str, values: &[ExplainedValue]) {
+    if values.is_empty() {
+        return;
+    }
+    lines.push(format!("{}{}:", indent, name));
+    for value in values {
+        push_text(
+            lines,
+            &format!("{}{}", indent, INDENT),
+            &format!("{}={}  ({})", value.key, value.value, value.origin),
+        );
+    }
+}
+
+impl ExplainedCmd {
+    /// Appends the command to the lines, followed by the tasks or commands it runs, if any
+    fn push_lines(&self, lines: &mut Vec<String>, indent: &str) {
+        match self {
+            ExplainedCmd::Cmd { path, cmd } => push_rendered(lines, indent, path, cmd),
+            ExplainedCmd::Task { path, task } => match task {
+                Ok(task) => {
+                    lines.push(format!("{}{}: task {}", indent, path, task.name));
+                    task.push_lines(lines, &format!("{}{}", indent, INDENT));
+                }
+                Err(reason) => push_text(lines, indent, &format!("{}: {}", path, reason)),
+            },
+            ExplainedCmd::Parallel { path, cmds } => {
+                lines.push(format!("{}{}: parallel", indent, path));
+                for cmd in cmds {
+                    cmd.push_lines(lines, &format!("{}{}", indent, INDENT));
+                }
+            }
+        }
+    }
+}
+
+impl TaskExplanation {
+    /// Appends the options of the task to the lines, without its name
+    fn push_lines(&self, lines: &mut Vec<String>, indent: &str) {
+        let nested = format!("{}{}", indent, INDENT);
+        lines.push(format!("{}file: {}", indent, self.path.display()));
+        if !self.extend.is_empty() {
+            lines.push(format!("{}extend: {}", indent, self.extend.join(", ")));
+        }
+        match &self.wd {
+            Some(wd) => lines.push(format!("{}wd: {}", indent, wd.display())),
+            None => lines.push(format!("{}wd: current directory", indent)),
+        }
+        push_values(lines, indent, "env", &self.env);
+        push_values(lines, indent, "vars", &self.vars);
+        if !self.params.is_empty() {
+            lines.push(format!("{}params:", indent));
+            for param in &self.params {
+                match &param.value {
+                    Some(value) => lines.push(format!("{}{}={}", nested, param.name, value)),
+                    None => lines.push(format!("{}{}  (required, missing)", nested, param.name)),
+                }
+            }
+        }
+        if !self.incl.is_empty() {
+            lines.push(format!("{}incl: {}", indent, self.incl.join(", ")));
+        }
+        match &self.run {
+            ExplainedRun::Script {
+                script_runner,
+                script,
+            } => {
+                push_rendered(lines, indent, "script_runner", script_runner);
+                match script {
+                    Ok(script) => {
+                        lines.push(format!("{}script:", indent));
+                        for line in script.lines() {
+                            lines.push(format!("{}{}", nested, line));
+                        }
+                    }
+                    Err(_) => push_rendered(lines, indent, "script", script),
+                }
+            }
+            ExplainedRun::Program { program, args } => {
+                lines.push(format!("{}program: {}", indent, program));
+                push_rendered(lines, indent, "args", args);
+            }
+            ExplainedRun::Cmds { parallel, cmds } => {
+                if *parallel {
+                    lines.push(format!("{}cmds (parallel):", indent));
+                } else {
+                    lines.push(format!("{}cmds:", indent));
+                }
+                for cmd in cmds {
+                    cmd.push_lines(lines, &nested);
+                }
+            }
+            ExplainedRun::Nothing => lines.push(format!("{}Nothing to run.", indent)),
+        }
+        if !self.finally.is_empty() {
+            lines.push(format!("{}finally:", indent));
+            for cmd in &self.finally {
+                cmd.push_lines(lines, &nested);
+            }
+        }
+    }
+}
+
+impl fmt::Display for TaskExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines = vec![self.name.clone()];
+        self.push_lines(&mut lines, INDENT);
+        write!(f, "{}", lines.join("\n"))
+    }
+}
@@ -0,0 +1,219 @@
+use super::*;
+use crate::mom_files_container::MomFilesContainer;
+use crate::overrides::Overrides;
+use crate::run_context::RunContext;
+use assert_fs::TempDir;
+use std::fs;
+
+#[test]
+fn test_explain() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::write(tmp_dir.join(".env"), "FROM_DOTENV=dotenv\nIN_FILE=dotenv\n").unwrap();
+    fs::write(
+        tmp_dir.join("base.yml"),
+        r#"
+version: 1
+
+env:
+    FROM_BASE_FILE: base_file
+"#,
+    )
+    .unwrap();
+    let mom_file_path = tmp_dir.join("mom.root.yml");
+    fs::write(
+        &mom_file_path,
+        r#"
+version: 1
+
+extend: base.yml
+dotenv: .env
+
+env:
+    IN_FILE: file
+
+vars:
+    target: debug
+
+tasks:
+    base:
+        env:
+            FROM_BASE: base
+
+    test:
+        cmds:
+            - echo {{ env.IN_BUILD }}
+
+    build:
+        extend: base
+        env:
+            IN_BUILD: build
+        params:
+            - name: profile
+              default: dev
+            - name: package
+              required: true
+        cmds:
+            - echo {{ vars.target }} {{ params.profile }}
+            - cmd: date
+              register: now
+            - echo {{ vars.now }} {{ vars.missing }}
+            - task: test
+"#,
+    )
+    .unwrap();
+
+    // Read through the container, so that the file is extended from its base
+    let mom_file_ptr = MomFilesContainer::new()
+        .read_mom_file(mom_file_path.clone())
+        .unwrap();
+    let mom_file = mom_file_ptr.lock().unwrap();
+    let overrides = Overrides::parse(&["vars.target=release"]).unwrap();
+    let ctx = RunContext::new(true).with_overrides(overrides);
+    let task = mom_file.get_task("build").unwrap();
+    let explanation = TaskExplanation::new(task, &mom_file, &ctx).unwrap();
+
+    let env: Vec<(&str, &str, String)> = explanation
+        .env
+        .iter()
+        .map(|value| {
+            (
+                value.key.as_str(),
+                value.value.as_str(),
+                value.origin.to_string(),
+            )
+        })
+        .collect();
+    let file = mom_file_path.display();
+    assert_eq!(
+        env,
+        vec![
+            ("FROM_BASE", "base", String::from("base task `base`")),
+            (
+                "FROM_BASE_FILE",
+                "base_file",
+                format!(
+                    "file {}, base file {}",
+                    file,
+                    tmp_dir.join("base.yml").display()
+                )
+            ),
+            (
+                "FROM_DOTENV",
+                "dotenv",
+                format!("file {}, dotenv {}", file, tmp_dir.join(".env").display())
+            ),
+            ("IN_BUILD", "build", String::from("task")),
+            ("IN_FILE", "file", format!("file {}", file)),
+        ]
+    );
+    assert_eq!(explanation.vars.len(), 1);
+    assert_eq!(explanation.vars[0].value, r#""release""#);
+    assert_eq!(explanation.vars[0].origin, Origin::Override);
+    // The default params are resolved even if a required one is missing
+    let params: Vec<(&str, Option<&str>)> = explanation
+        .params
+        .iter()
+        .map(|param| (param.name.as_str(), param.value.as_deref()))
+        .collect();
+    assert_eq!(
+        params,
+        vec![("profile", Some(r#""dev""#)), ("package", None)]
+    );
+
+    let cmds = match &explanation.run {
+        ExplainedRun::Cmds { cmds, .. } => cmds,
+        run => panic!("Unexpected run {:?}", run),
+    };
+    match &cmds[0] {
+        ExplainedCmd::Cmd { path, cmd } => {
+            assert_eq!(path, "cmds.0");
+            assert_eq!(cmd.as_deref(), Ok("echo release dev"));
+        }
+        cmd => panic!("Unexpected cmd {:?}", cmd),
+    }
+    // The output of the registered commands is not known, and missing vars cannot be rendered
+    match &cmds[2] {
+        ExplainedCmd::Cmd {
+            cmd: Err(reason), ..
+        } => {
+            assert!(reason.contains("Variable `vars.missing` not found"))
+        }
+        cmd => panic!("Unexpected cmd {:?}", cmd),
+    }
+    match &cmds[3] {
+        ExplainedCmd::Task { task: Ok(task), .. } => {
+            assert_eq!(task.name, "build.cmds.3.test");
+            let value = task
+                .env
+                .iter()
+                .find(|value| value.key == "IN_BUILD")
+                .unwrap();
+            assert_eq!(value.origin.to_string(), "parent task `build`");
+        }
+        cmd => panic!("Unexpected cmd {:?}", cmd),
+    }
+}
+
+#[test]
+fn test_explanation_display() {
+    let explanation = TaskExplanation {
+        name: String::from("build"),
+        path: PathBuf::from("mom.root.yml"),
+        extend: vec![String::from("base")],
+        wd: None,
+        env: vec![ExplainedValue {
+            key: String::from("KEY"),
+            value: String::from("value"),
+            origin: Origin::BaseTask(
+                String::from("base"),
+                Box::new(Origin::Dotenv(PathBuf::from(".env"))),
+            ),
+        }],
+        vars: Vec::new(),
+        params: vec![
+            ExplainedParam {
+                name: String::from("profile"),
+                value: Some(String::from(r#""dev""#)),
+            },
+            ExplainedParam {
+                name: String::from("package"),
+                value: None,
+            },
+        ],
+        incl: Vec::new(),
+        run: ExplainedRun::Cmds {
+            parallel: false,
+            cmds: vec![
+                ExplainedCmd::Cmd {
+                    path: String::from("cmds.0"),
+                    cmd: Ok(String::from("echo hello")),
+                },
+                ExplainedCmd::Parallel {
+                    path: String::from("cmds.1"),
+                    cmds: vec![ExplainedCmd::Cmd {
+                        path: String::from("cmds.1.0"),
+                        cmd: Err(String::from("Failed to render\nCaused by: missing")),
+                    }],
+                },
+            ],
+        },
+        finally: Vec::new(),
+    };
+    assert_eq!(
+        explanation.to_string(),
+        r#"build
+  file: mom.root.yml
+  extend: base
+  wd: current directory
+  env:
+    KEY=value  (base task `base`, dotenv .env)
+  params:
+    profile="dev"
+    package  (required, missing)
+  cmds:
+    cmds.0: echo hello
+    cmds.1: parallel
+      cmds.1.0: cannot render: Failed to render
+        Caused by: missing"#
+    );
+}
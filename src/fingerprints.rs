@@ -0,0 +1,169 @@
+#[cfg(test)]
+#[path = "fingerprints_test.rs"]
+mod fingerprints_test;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+
+use crate::serde_common::StringOrVecString;
+use crate::types::DynErrResult;
+use crate::utils::get_path_relative_to_base;
+
+/// Folder, next to the mom file, where mom stores its state.
+pub(crate) const STATE_FOLDER_NAME: &str = ".mom";
+
+/// Folder inside the state folder where the fingerprints are stored.
+const FINGERPRINTS_FOLDER_NAME: &str = "fingerprints";
+
+/// Method used to detect changes in the sources of a task.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FingerprintMethod {
+    /// Hash of the content of the files
+    #[default]
+    Checksum,
+    /// Modification time and size of the files
+    Timestamp,
+}
+
+/// Returns the files matching the given glob patterns, sorted and without duplicates.
+///
+/// # Arguments
+///
+/// * `base_path`: Path the patterns are relative to
+/// * `patterns`: Glob patterns to match
+pub(crate) fn get_matching_files(
+    base_path: &Path,
+    patterns: &StringOrVecString,
+) -> DynErrResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for pattern in patterns.iter() {
+        let pattern = get_path_relative_to_base(base_path, pattern);
+        let pattern = pattern.to_string_lossy();
+        let paths = glob::glob(&pattern)
+            .map_err(|e| format!("Invalid glob pattern `{}`: {}", pattern, e))?;
+        for path in paths {
+            let path = path?;
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Returns whether each of the given glob patterns matches at least one file.
+///
+/// # Arguments
+///
+/// * `base_path`: Path the patterns are relative to
+/// * `patterns`: Glob patterns to match
+pub(crate) fn all_patterns_match(
+    base_path: &Path,
+    patterns: &StringOrVecString,
+) -> DynErrResult<bool> {
+    for pattern in patterns.iter() {
+        let pattern = StringOrVecString::Single(pattern.to_string());
+        if get_matching_files(base_path, &pattern)?.is_empty() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Returns a fingerprint of the files matching the given glob patterns, which changes when
+/// a file is added, removed or modified.
+///
+/// # Arguments
+///
+/// * `base_path`: Path the patterns are relative to
+/// * `patterns`: Glob patterns of the files
+/// * `method`: Method used to detect changes in the files
+pub(crate) fn get_fingerprint(
+    base_path: &Path,
+    patterns: &StringOrVecString,
+    method: FingerprintMethod,
+) -> DynErrResult<String> {
+    let mut hasher = Md5::new();
+    for path in get_matching_files(base_path, patterns)? {
+        hasher.update(path.to_string_lossy().as_bytes());
+        match method {
+            FingerprintMethod::Checksum => {
+                hasher.update(fs::read(&path)?);
+            }
+            FingerprintMethod::Timestamp => {
+                let metadata = fs::metadata(&path)?;
+                let modified = metadata.modified()?.duration_since(UNIX_EPOCH)?;
+                hasher.update(modified.as_nanos().to_le_bytes());
+                hasher.update(metadata.len().to_le_bytes());
+            }
+        }
+    }
+    Ok(format!("{:X}", hasher.finalize()))
+}
+
+/// Returns a fingerprint of the given definition, which changes when any of its values changes.
+///
+/// # Arguments
+///
+/// * `definition`: Definition to fingerprint, i.e. a task along with its env, vars and arguments
+pub(crate) fn get_definition_fingerprint<T: Serialize>(definition: &T) -> DynErrResult<String> {
+    // The maps of serde_json are sorted, so the order of the keys in the definition does not matter
+    let value = serde_json::to_value(definition)?;
+    let mut hasher = Md5::new();
+    hasher.update(value.to_string().as_bytes());
+    Ok(format!("{:X}", hasher.finalize()))
+}
+
+/// Returns the path where the fingerprint of the given task is stored.
+fn get_fingerprint_path(mom_file_path: &Path, task_name: &str) -> PathBuf {
+    let mut hasher = Md5::new();
+    hasher.update(mom_file_path.to_string_lossy().as_bytes());
+    hasher.update(task_name.as_bytes());
+    let file_name = format!("{:X}", hasher.finalize());
+
+    let mut path = match mom_file_path.parent() {
+        Some(directory) => directory.to_path_buf(),
+        None => PathBuf::new(),
+    };
+    path.push(STATE_FOLDER_NAME);
+    path.push(FINGERPRINTS_FOLDER_NAME);
+    path.push(file_name);
+    path
+}
+
+/// Returns the fingerprint stored after the last successful run of the given task, if any.
+///
+/// # Arguments
+///
+/// * `mom_file_path`: Path of the mom file the task belongs to
+/// * `task_name`: Name of the task
+pub(crate) fn read_fingerprint(mom_file_path: &Path, task_name: &str) -> Option<String> {
+    fs::read_to_string(get_fingerprint_path(mom_file_path, task_name)).ok()
+}
+
+/// Stores the fingerprint of the given task in the state folder next to the mom file.
+///
+/// # Arguments
+///
+/// * `mom_file_path`: Path of the mom file the task belongs to
+/// * `task_name`: Name of the task
+/// * `fingerprint`: Fingerprint to store
+pub(crate) fn write_fingerprint(
+    mom_file_path: &Path,
+    task_name: &str,
+    fingerprint: &str,
+) -> DynErrResult<()> {
+    let path = get_fingerprint_path(mom_file_path, task_name);
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(path, fingerprint)?;
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use super::*;
+use assert_fs::TempDir;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+
+#[test]
+fn test_get_matching_files() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::create_dir(tmp_dir.join("src")).unwrap();
+    File::create(tmp_dir.join("src").join("a.rs")).unwrap();
+    File::create(tmp_dir.join("src").join("b.rs")).unwrap();
+    File::create(tmp_dir.join("README.md")).unwrap();
+
+    let patterns = StringOrVecString::Multiple(vec![
+        String::from("src/*.rs"),
+        String::from("src/a.rs"),
+        String::from("*.md"),
+    ]);
+    let files = get_matching_files(tmp_dir.path(), &patterns).unwrap();
+    assert_eq!(
+        files,
+        vec![
+            tmp_dir.join("README.md"),
+            tmp_dir.join("src").join("a.rs"),
+            tmp_dir.join("src").join("b.rs"),
+        ]
+    );
+
+    assert!(all_patterns_match(tmp_dir.path(), &patterns).unwrap());
+    let patterns =
+        StringOrVecString::Multiple(vec![String::from("src/*.rs"), String::from("target/*")]);
+    assert!(!all_patterns_match(tmp_dir.path(), &patterns).unwrap());
+}
+
+#[test]
+fn test_checksum_fingerprint() {
+    let tmp_dir = TempDir::new().unwrap();
+    let patterns = StringOrVecString::Single(String::from("*.txt"));
+    let empty_fingerprint =
+        get_fingerprint(tmp_dir.path(), &patterns, FingerprintMethod::Checksum).unwrap();
+
+    let mut file = File::create(tmp_dir.join("a.txt")).unwrap();
+    file.write_all(b"hello").unwrap();
+    let fingerprint =
+        get_fingerprint(tmp_dir.path(), &patterns, FingerprintMethod::Checksum).unwrap();
+    assert_ne!(fingerprint, empty_fingerprint);
+
+    let same_fingerprint =
+        get_fingerprint(tmp_dir.path(), &patterns, FingerprintMethod::Checksum).unwrap();
+    assert_eq!(fingerprint, same_fingerprint);
+
+    file.write_all(b" world").unwrap();
+    let new_fingerprint =
+        get_fingerprint(tmp_dir.path(), &patterns, FingerprintMethod::Checksum).unwrap();
+    assert_ne!(fingerprint, new_fingerprint);
+}
+
+#[test]
+fn test_timestamp_fingerprint() {
+    let tmp_dir = TempDir::new().unwrap();
+    let patterns = StringOrVecString::Single(String::from("*.txt"));
+
+    let mut file = File::create(tmp_dir.join("a.txt")).unwrap();
+    file.write_all(b"hello").unwrap();
+    let fingerprint =
+        get_fingerprint(tmp_dir.path(), &patterns, FingerprintMethod::Timestamp).unwrap();
+
+    let same_fingerprint =
+        get_fingerprint(tmp_dir.path(), &patterns, FingerprintMethod::Timestamp).unwrap();
+    assert_eq!(fingerprint, same_fingerprint);
+
+    // The size changes, even if the modification time has a low resolution
+    file.write_all(b" world").unwrap();
+    let new_fingerprint =
+        get_fingerprint(tmp_dir.path(), &patterns, FingerprintMethod::Timestamp).unwrap();
+    assert_ne!(fingerprint, new_fingerprint);
+}
+
+#[test]
+fn test_read_write_fingerprint() {
+    let tmp_dir = TempDir::new().unwrap();
+    let mom_file_path = tmp_dir.join("mom.root.yml");
+
+    assert_eq!(read_fingerprint(&mom_file_path, "build"), None);
+
+    write_fingerprint(&mom_file_path, "build", "ABC").unwrap();
+    assert_eq!(
+        read_fingerprint(&mom_file_path, "build"),
+        Some(String::from("ABC"))
+    );
+    assert_eq!(read_fingerprint(&mom_file_path, "test"), None);
+    assert!(tmp_dir.join(STATE_FOLDER_NAME).is_dir());
+}
+
+#[test]
+fn test_definition_fingerprint() {
+    let definition = HashMap::from([("a", "1"), ("b", "2"), ("c", "3")]);
+    let fingerprint = get_definition_fingerprint(&definition).unwrap();
+
+    // The order of the keys does not matter
+    let same_definition = HashMap::from([("c", "3"), ("b", "2"), ("a", "1")]);
+    assert_eq!(
+        get_definition_fingerprint(&same_definition).unwrap(),
+        fingerprint
+    );
+
+    let new_definition = HashMap::from([("a", "1"), ("b", "2"), ("c", "4")]);
+    assert_ne!(
+        get_definition_fingerprint(&new_definition).unwrap(),
+        fingerprint
+    );
+}
@@ -0,0 +1,221 @@
+#[cfg(test)]
+#[path = "init_test.rs"]
+mod init_test;
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+
+use crate::types::DynErrResult;
+
+/// Comment that lets editors using the YAML language server validate the file with the schema
+const SCHEMA_COMMENT: &str =
+    "# yaml-language-server: $schema=https://raw.githubusercontent.com/adrianmrit/mom/main/json-schema/mom.json";
+
+/// Name of the mom file created by default
+pub(crate) const DEFAULT_MOM_FILE_NAME: &str = "mom.root.yml";
+
+/// Task added to the template
+struct TemplateTask {
+    /// Help of the task
+    help: String,
+    /// Command the task runs
+    cmd: String,
+}
+
+/// Tasks detected from a project file, i.e. `Cargo.toml`
+struct DetectedTasks {
+    /// Name of the file the tasks were detected from
+    source: &'static str,
+    /// Tasks by name
+    tasks: IndexMap<String, TemplateTask>,
+}
+
+impl DetectedTasks {
+    fn new(source: &'static str) -> Self {
+        DetectedTasks {
+            source,
+            tasks: IndexMap::new(),
+        }
+    }
+
+    /// Adds a task, unless its name is not a valid task name.
+    fn add(&mut self, name: &str, help: &str, cmd: &str) {
+        lazy_static! {
+            static ref TASK_NAME_REGEX: regex::Regex =
+                regex::Regex::new(r"^[_a-zA-Z][a-zA-Z0-9_-]*$").unwrap();
+        }
+        if TASK_NAME_REGEX.is_match(name) {
+            self.tasks
+                .entry(String::from(name))
+                .or_insert(TemplateTask {
+                    help: String::from(help),
+                    cmd: String::from(cmd),
+                });
+        }
+    }
+}
+
+/// Returns the tasks of a Rust project.
+fn detect_cargo_tasks(dir: &Path) -> Option<DetectedTasks> {
+    if !dir.join("Cargo.toml").is_file() {
+        return None;
+    }
+    let mut detected = DetectedTasks::new("Cargo.toml");
+    detected.add("build", "Builds the project", "cargo build");
+    detected.add("test", "Runs the tests", "cargo test");
+    detected.add(
+        "lint",
+        "Checks the code with clippy",
+        "cargo clippy --all-targets -- -D warnings",
+    );
+    detected.add("fmt", "Formats the code", "cargo fmt");
+    Some(detected)
+}
+
+/// Returns the tasks of a Node project, one for each of its scripts.
+fn detect_npm_tasks(dir: &Path) -> Option<DetectedTasks> {
+    let content = fs::read_to_string(dir.join("package.json")).ok()?;
+    let mut detected = DetectedTasks::new("package.json");
+    detected.add("install", "Installs the dependencies", "npm install");
+    let package: serde_json::Value = serde_json::from_str(&content).unwrap_or_default();
+    if let Some(scripts) = package
+        .get("scripts")
+        .and_then(|scripts| scripts.as_object())
+    {
+        for name in scripts.keys() {
+            detected.add(
+                name,
+                &format!("Runs the {} script", name),
+                &format!("npm run {}", name),
+            );
+        }
+    }
+    Some(detected)
+}
+
+/// Returns the tasks of a Python project.
+fn detect_python_tasks(dir: &Path) -> Option<DetectedTasks> {
+    if !dir.join("pyproject.toml").is_file() {
+        return None;
+    }
+    let mut detected = DetectedTasks::new("pyproject.toml");
+    detected.add(
+        "install",
+        "Installs the project in editable mode",
+        "pip install -e .",
+    );
+    detected.add("test", "Runs the tests", "python -m pytest");
+    Some(detected)
+}
+
+/// Returns the tasks of a project using make, one for each of the targets of the Makefile.
+fn detect_make_tasks(dir: &Path) -> Option<DetectedTasks> {
+    lazy_static! {
+        // Ignores variable assignments, i.e. `CC := gcc`, and special targets, i.e. `.PHONY`
+        static ref TARGET_REGEX: regex::Regex =
+            regex::Regex::new(r"^([_a-zA-Z][a-zA-Z0-9_-]*)\s*:([^=]|$)").unwrap();
+    }
+    let content = fs::read_to_string(dir.join("Makefile")).ok()?;
+    let mut detected = DetectedTasks::new("Makefile");
+    for line in content.lines() {
+        if let Some(captures) = TARGET_REGEX.captures(line) {
+            let target = &captures[1];
+            detected.add(
+                target,
+                &format!("Runs the {} target", target),
+                &format!("make {}", target),
+            );
+        }
+    }
+    Some(detected)
+}
+
+/// Returns a YAML scalar with the given value, quoted if needed.
+fn to_yaml_scalar(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|value| value.trim_end().to_string())
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+/// Returns the content of a new mom file. If `detect` is set, tasks are added for the
+/// `Cargo.toml`, `package.json`, `pyproject.toml` and `Makefile` files found in `dir`.
+/// Otherwise, or if none is found, an example task is added instead.
+///
+/// # Arguments
+///
+/// * `dir`: Directory the mom file is created in
+/// * `detect`: Whether to add tasks for the project files found in `dir`
+pub(crate) fn get_template(dir: &Path, detect: bool) -> String {
+    let mut detected: Vec<DetectedTasks> = Vec::new();
+    if detect {
+        let detectors = [
+            detect_cargo_tasks,
+            detect_npm_tasks,
+            detect_python_tasks,
+            detect_make_tasks,
+        ];
+        for detector in detectors {
+            if let Some(mut tasks) = detector(dir) {
+                // Tasks detected first take precedence, i.e. `test` from Cargo.toml over the
+                // one from the Makefile
+                tasks.tasks.retain(|name, _| {
+                    !detected
+                        .iter()
+                        .any(|previous| previous.tasks.contains_key(name))
+                });
+                if !tasks.tasks.is_empty() {
+                    detected.push(tasks);
+                }
+            }
+        }
+    }
+
+    if detected.is_empty() {
+        let mut example = DetectedTasks::new("");
+        example.add("hello", "Says hello", "echo \"Hello from mom!\"");
+        detected.push(example);
+    }
+
+    let mut template = format!("{}\nversion: 1\n\ntasks:", SCHEMA_COMMENT);
+    for tasks in detected {
+        template.push('\n');
+        if !tasks.source.is_empty() {
+            template.push_str(&format!("  # Detected from {}\n", tasks.source));
+        }
+        for (name, task) in tasks.tasks {
+            template.push_str(&format!(
+                "  {}:\n    help: {}\n    cmds:\n      - {}\n",
+                name,
+                to_yaml_scalar(&task.help),
+                to_yaml_scalar(&task.cmd)
+            ));
+        }
+    }
+    template
+}
+
+/// Creates a mom file at the given path from the template, refusing to overwrite an existing
+/// file. The parent folders are created if they do not exist.
+///
+/// # Arguments
+///
+/// * `path`: Path of the mom file to create
+/// * `detect`: Whether to add tasks for the project files found in the folder of the file
+pub(crate) fn init_mom_file(path: &Path, detect: bool) -> DynErrResult<()> {
+    if path.exists() {
+        return Err(format!("{} already exists, not overwriting it.", path.display()).into());
+    }
+    let dir = path.parent().unwrap_or(Path::new(""));
+    if !dir.as_os_str().is_empty() {
+        fs::create_dir_all(dir)?;
+    }
+    let template = get_template(dir, detect);
+    // Fails if the file was created in the meantime
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(template.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use super::*;
+use crate::mom_files::MomFile;
+use assert_fs::TempDir;
+
+#[test]
+fn test_default_template() {
+    let tmp_dir = TempDir::new().unwrap();
+    let template = get_template(tmp_dir.path(), true);
+    assert!(template.starts_with(SCHEMA_COMMENT));
+
+    let mom_file = MomFile::from_str(&template).unwrap();
+    assert_eq!(mom_file.get_public_task_names(), vec!["hello"]);
+}
+
+#[test]
+fn test_detected_template() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::write(tmp_dir.join("Cargo.toml"), "[package]\nname = \"sample\"\n").unwrap();
+    fs::write(
+        tmp_dir.join("package.json"),
+        r#"{"scripts": {"build": "tsc", "build:prod": "tsc -p prod", "dev": "vite"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        tmp_dir.join("pyproject.toml"),
+        "[project]\nname = \"sample\"\n",
+    )
+    .unwrap();
+    fs::write(
+        tmp_dir.join("Makefile"),
+        "CC := gcc\n.PHONY: docs\ndocs: build\n\tmkdocs build\nclean :\n\trm -rf build\n",
+    )
+    .unwrap();
+
+    let template = get_template(tmp_dir.path(), true);
+    let mom_file = MomFile::from_str(&template).unwrap();
+    let mut task_names = mom_file.get_public_task_names();
+    task_names.sort();
+    assert_eq!(
+        task_names,
+        vec!["build", "clean", "dev", "docs", "fmt", "install", "lint", "test"]
+    );
+
+    // The tasks detected first take precedence
+    let task = mom_file.clone_task("build").unwrap();
+    assert_eq!(task.get_help(), "Builds the project");
+    assert!(template.contains("      - cargo build\n"));
+    assert!(template.contains("      - npm install\n"));
+    assert!(template.contains("      - make docs\n"));
+
+    // Without detection, only the example task is added
+    let template = get_template(tmp_dir.path(), false);
+    let mom_file = MomFile::from_str(&template).unwrap();
+    assert_eq!(mom_file.get_public_task_names(), vec!["hello"]);
+}
+
+#[test]
+fn test_init_mom_file() {
+    let tmp_dir = TempDir::new().unwrap();
+    let path = tmp_dir.join("nested").join("mom.root.yml");
+    init_mom_file(&path, true).unwrap();
+    assert!(MomFile::from_path(path.clone()).is_ok());
+
+    let err = init_mom_file(&path, true).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("{} already exists, not overwriting it.", path.display())
+    );
+}
@@ -5,14 +5,28 @@ pub mod cli;
 
 pub(crate) mod args;
 pub(crate) mod builtin_commands;
+pub(crate) mod command_options;
+#[cfg(feature = "runtime")]
+pub(crate) mod completions;
 mod defaults;
+pub(crate) mod diagnostics;
+pub(crate) mod dynamic_values;
 pub(crate) mod errors;
+pub(crate) mod explain;
+pub(crate) mod fingerprints;
+#[cfg(feature = "runtime")]
+pub(crate) mod init;
 pub(crate) mod mom_file_paths;
 pub(crate) mod mom_files;
 pub(crate) mod mom_files_container;
+pub(crate) mod overrides;
+pub(crate) mod params;
 pub mod print_utils;
+pub(crate) mod run_context;
 pub(crate) mod serde_common;
 pub mod tasks;
 pub(crate) mod tera;
 pub(crate) mod types;
 mod utils;
+#[cfg(feature = "runtime")]
+pub(crate) mod watch;
@@ -2,7 +2,7 @@
 use mom_task::print_utils::MomOutput;
 
 #[cfg(feature = "runtime")]
-use mom_task::cli::exec;
+use mom_task::cli::{exec, get_exit_code};
 
 #[cfg(feature = "runtime")]
 fn main() {
@@ -10,7 +10,7 @@ fn main() {
         Ok(_) => {}
         Err(e) => {
             eprint!("{}", e.to_string().mom_error());
-            std::process::exit(1);
+            std::process::exit(get_exit_code(e.as_ref()));
         }
     }
 }
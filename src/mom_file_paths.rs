@@ -109,7 +109,6 @@ impl MomFilePaths {
 
 /// Single mom file path iterator. This iterator will only return the given path
 /// if it exists and is a file, otherwise it will return None.
-
 pub(crate) struct SingleMomFilePath {
     path: PathBuf,
     ended: bool,
@@ -120,8 +119,8 @@ impl SingleMomFilePath {
     /// If the path does not exist or is not a file, the iterator will return None.
     /// # Arguments
     /// * `path`: Path to start searching for mom files.
+    ///
     /// returns: SingleMomFilePath
-
     pub(crate) fn new<S: AsRef<OsStr> + ?Sized>(path: &S) -> Box<Self> {
         Box::new(SingleMomFilePath {
             path: PathBuf::from(path),
@@ -147,6 +146,11 @@ impl Iterator for SingleMomFilePath {
     }
 }
 
+/// Returns the path where the global mom file is created, if the home directory is known.
+pub(crate) fn get_default_global_mom_file_path() -> Option<PathBuf> {
+    UserDirs::new().map(|user_dirs| user_dirs.home_dir().join(GLOBAL_MOM_FILES_PRIO[0]))
+}
+
 /// Iterator that returns the first existing global mom file path.
 pub(crate) struct GlobalMomFilePath {
     ended: bool,
@@ -154,7 +158,6 @@ pub(crate) struct GlobalMomFilePath {
 
 impl GlobalMomFilePath {
     /// Initializes GlobalMomFilePath.
-
     pub(crate) fn new() -> Box<Self> {
         Box::new(GlobalMomFilePath { ended: false })
     }
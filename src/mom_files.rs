@@ -3,20 +3,33 @@
 mod mom_files_test;
 
 use crate::cli::Version;
+use crate::diagnostics::{
+    find_task_position, find_yaml_path, from_setup_error, from_yaml_error, render_snippet, Position,
+};
+use crate::errors::{AwareTaskError, MissingTaskError, ReadFileError, TaskError};
 use crate::merge_map_values;
-use crate::serde_common::CommonFields;
+use crate::serde_common::{CommonFields, Origin};
 use crate::tasks::Task;
+use crate::tera::get_tera_instance;
 use crate::types::DynErrResult;
-use crate::utils::{get_task_dependency_graph, to_os_task_name};
+use crate::utils::{
+    find_cycle, get_missing_task_errors, get_task_dependency_graph, to_os_task_name,
+};
 use lazy_static::lazy_static;
 use petgraph::algo::toposort;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::{env, fs};
 
+/// Pattern of the names of tasks, without the OS suffix, and of the namespaces of included files.
+/// They cannot contain `:`, which separates the namespace from the name of the task.
+const NAME_PATTERN: &str = r"[_a-zA-Z][a-zA-Z0-9_-]*";
+
 /// Represents a mom file.
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MomFile {
     /// Version of the mom file.
@@ -35,6 +48,16 @@ pub(crate) struct MomFile {
     #[serde(default)]
     #[serde(deserialize_with = "deserialize_tasks")]
     pub(crate) tasks: HashMap<String, Task>,
+
+    /// Paths of the mom files to include, by namespace. Their tasks can be called as
+    /// `namespace:task`.
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_include")]
+    pub(crate) include: BTreeMap<String, String>,
+
+    /// Included mom files, by namespace. Loaded by `MomFilesContainer`.
+    #[serde(skip)]
+    pub(crate) included: BTreeMap<String, Arc<MomFile>>,
 }
 
 fn deserialize_tasks<'de, D>(deserializer: D) -> Result<HashMap<String, Task>, D::Error>
@@ -56,7 +79,7 @@ where
         {
             lazy_static! {
                 static ref KEY_REGEX: regex::Regex =
-                    regex::Regex::new(r"^[_a-zA-Z][a-zA-Z0-9_-]*(\.(windows|linux|macos))?$")
+                    regex::Regex::new(&format!(r"^{}(\.(windows|linux|macos))?$", NAME_PATTERN))
                         .unwrap();
             }
             let mut tasks = HashMap::new();
@@ -78,6 +101,87 @@ where
     deserializer.deserialize_map(TaskVisitor)
 }
 
+fn deserialize_include<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    lazy_static! {
+        static ref NAMESPACE_REGEX: regex::Regex =
+            regex::Regex::new(&format!("^{}$", NAME_PATTERN)).unwrap();
+    }
+    let include = BTreeMap::<String, String>::deserialize(deserializer)?;
+    for namespace in include.keys() {
+        if !NAMESPACE_REGEX.is_match(namespace) {
+            return Err(serde::de::Error::custom(format!(
+                "Invalid namespace `{}`. Namespaces must start with a letter or underscore and can only \
+                contain letters, numbers, underscores and dashes.",
+                namespace
+            )));
+        }
+    }
+    Ok(include)
+}
+
+/// Problem found when checking a mom file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Problem {
+    /// Name of the task the problem is in, if any
+    pub(crate) task_name: Option<String>,
+    /// What the problem is, to tell whether it is the same as the error loading the mom file
+    pub(crate) kind: ProblemKind,
+    /// Description of the problem, followed by a snippet of the mom file pointing to it, if it
+    /// can be located
+    pub(crate) description: String,
+}
+
+/// Kind of a problem found when checking a mom file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ProblemKind {
+    /// Invalid configuration or template of a task
+    Task(TaskError),
+    /// Task referred to by the task that does not exist
+    MissingTask(String),
+    /// Env or vars file of the mom file that cannot be read
+    File(PathBuf),
+    /// Invalid `incl` template of the mom file, by name
+    Incl(String),
+}
+
+impl Problem {
+    /// Returns whether the error a mom file failed to load with is this problem. Errors that
+    /// checking does not find, i.e. cyclic dependencies, are never the same.
+    ///
+    /// # Arguments
+    ///
+    /// * `error`: Error the mom file failed to load with
+    pub(crate) fn is_same_as(&self, error: &(dyn Error + 'static)) -> bool {
+        let mut error = Some(error);
+        while let Some(current) = error {
+            if let Some((task_name, kind)) = Problem::get_task_and_kind(current) {
+                return self.task_name.as_deref() == task_name && self.kind == kind;
+            }
+            error = current.source();
+        }
+        false
+    }
+
+    /// Returns the task and the kind of problem an error is, if checking can find it.
+    fn get_task_and_kind<'a>(
+        error: &'a (dyn Error + 'static),
+    ) -> Option<(Option<&'a str>, ProblemKind)> {
+        if let Some(e) = error.downcast_ref::<AwareTaskError>() {
+            let kind = ProblemKind::Task(e.error.clone());
+            return Some((Some(e.task_name.as_str()), kind));
+        }
+        if let Some(e) = error.downcast_ref::<MissingTaskError>() {
+            let kind = ProblemKind::MissingTask(e.missing_name.clone());
+            return Some((Some(e.task_name.as_str()), kind));
+        }
+        let e = error.downcast_ref::<ReadFileError>()?;
+        Some((None, ProblemKind::File(e.path.clone())))
+    }
+}
+
 impl MomFile {
     /// Reads the file from the path and constructs a mom file
     fn deserialize_from_path(path: &Path) -> DynErrResult<MomFile> {
@@ -85,7 +189,7 @@ impl MomFile {
             Ok(file_contents) => file_contents,
             Err(e) => return Err(format!("There was an error reading the file:\n{}", e).into()),
         };
-        Ok(serde_yaml::from_str(&contents)?)
+        serde_yaml::from_str(&contents).map_err(|e| from_yaml_error(e, path, &contents))
     }
 
     /// Reads the file from the string and constructs a mom file
@@ -98,7 +202,9 @@ impl MomFile {
         let mut mom_file = MomFile::deserialize_from_path(path.as_path())?;
         mom_file.filepath = path;
         mom_file.directory = PathBuf::from(mom_file.filepath.parent().unwrap());
-        mom_file.setup()?;
+        mom_file
+            .setup()
+            .map_err(|e| from_setup_error(e, &mom_file.filepath))?;
         Ok(mom_file)
     }
 
@@ -121,13 +227,17 @@ impl MomFile {
 
         let dep_graph = get_task_dependency_graph(&tasks)?;
 
-        // TODO: Return the cycle. Could use petgraph::visit::DfsPostOrder instead of toposort
         let dependencies = toposort(&dep_graph, None);
 
         let dependencies = match dependencies {
             Ok(dependencies) => dependencies,
             Err(e) => {
-                return Err(format!("Found a cyclic dependency for task: {}", e.node_id()).into());
+                let cycle = find_cycle(&dep_graph, e.node_id()).unwrap_or(vec![e.node_id()]);
+                return Err(format!(
+                    "Found a cyclic dependency for task: {}",
+                    self.describe_cycle(&cycle)
+                )
+                .into());
             }
         };
 
@@ -167,9 +277,234 @@ impl MomFile {
         Ok(())
     }
 
+    /// Returns the tasks of a cycle joined by arrows, followed by the mom file they are declared
+    /// in, if known, i.e. `a -> b -> a (in mom.root.yml)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cycle`: Names of the tasks that form the cycle, with the first one repeated at the end
+    fn describe_cycle(&self, cycle: &[&str]) -> String {
+        let mut description = cycle.join(" -> ");
+        if !self.filepath.as_os_str().is_empty() {
+            description.push_str(&format!(" (in {})", self.filepath.display()));
+        }
+        description
+    }
+
     pub(crate) fn extend(&mut self, other: &MomFile) {
-        self.common.extend(&other.common);
+        self.common.extend(&other.common, |origin| {
+            Origin::BaseFile(other.filepath.clone(), Box::new(origin))
+        });
         merge_map_values!(self.tasks, &other.tasks);
+        merge_map_values!(self.included, &other.included);
+    }
+
+    /// Returns the mom file a task belongs to, and the name of the task inside it. Tasks of
+    /// included mom files are given as `namespace:task`, where the task can be namespaced as
+    /// well if the included file includes other files. Returns None if the namespace does not
+    /// exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_name`: Name of the task, possibly namespaced
+    pub(crate) fn resolve_namespace<'a, 'b>(
+        &'a self,
+        task_name: &'b str,
+    ) -> Option<(&'a MomFile, &'b str)> {
+        match task_name.split_once(':') {
+            Some((namespace, task_name)) => self
+                .included
+                .get(namespace)
+                .and_then(|mom_file| mom_file.resolve_namespace(task_name)),
+            None => Some((self, task_name)),
+        }
+    }
+
+    /// Checks the mom file and its tasks, including the OS specific ones, without running them,
+    /// and returns the problems found, i.e. invalid templates or tasks of included files that do
+    /// not exist. Expected to be called on a loaded mom file, as the tasks are not validated
+    /// otherwise.
+    pub(crate) fn check(&self) -> Vec<Problem> {
+        // Only used to point to the problems
+        let source = fs::read_to_string(&self.filepath).ok();
+        let mut problems = self.check_incl(source.as_deref());
+        let mut task_names: Vec<&String> = self.tasks.keys().collect();
+        task_names.sort();
+        for name in task_names {
+            let task = &self.tasks[name];
+            for problem in task.check(self) {
+                problems.push(self.describe_task_problem(source.as_deref(), name, problem));
+            }
+            // The tasks of this file are checked when loading it, but not the included ones
+            for included_name in task.get_included_dependencies() {
+                let found = self
+                    .resolve_namespace(included_name)
+                    .and_then(|(mom_file, name)| mom_file.get_task(name));
+                if found.is_none() {
+                    let error = MissingTaskError::new(name, included_name);
+                    problems.push(self.describe_missing_task(source.as_deref(), error));
+                }
+            }
+        }
+        problems
+    }
+
+    /// Reads the mom file from the path without loading it, and returns all the problems found
+    /// in it, including every task it refers to that does not exist. Loading a mom file stops at
+    /// the first problem, so this is used to report the rest when it fails. Returns an error if
+    /// the file cannot be parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path of the mom file to check
+    pub(crate) fn check_path(path: &Path) -> DynErrResult<Vec<Problem>> {
+        let mut mom_file = MomFile::deserialize_from_path(path)?;
+        mom_file.filepath = PathBuf::from(path);
+        mom_file.directory = PathBuf::from(path.parent().unwrap());
+        let source = fs::read_to_string(path).ok();
+        let source = source.as_deref();
+
+        let mut problems: Vec<Problem> = mom_file
+            .common
+            .check_files(&mom_file.directory)
+            .into_iter()
+            .map(|error| Problem {
+                task_name: None,
+                description: error.to_string(),
+                kind: ProblemKind::File(error.path),
+            })
+            .collect();
+        problems.extend(mom_file.check_incl(source));
+        let mut task_names: Vec<&String> = mom_file.tasks.keys().collect();
+        task_names.sort();
+        let mut flat_tasks = HashMap::new();
+        for name in task_names {
+            let task = &mom_file.tasks[name];
+            let os_tasks = [
+                ("linux", &task.linux),
+                ("windows", &task.windows),
+                ("macos", &task.macos),
+            ];
+            let os_tasks = os_tasks.into_iter().filter_map(|(os, os_task)| {
+                os_task
+                    .as_ref()
+                    .map(|os_task| (format!("{}.{}", name, os), os_task.as_ref()))
+            });
+            for (name, task) in std::iter::once((name.clone(), task)).chain(os_tasks) {
+                // Loading fails with these as configuration errors of the task
+                for error in task.common.check_files(&mom_file.directory) {
+                    problems.push(Problem {
+                        task_name: Some(name.clone()),
+                        description: format!("Task `{}`: {}", name, error),
+                        kind: ProblemKind::Task(TaskError::ConfigError(error.to_string())),
+                    });
+                }
+                for problem in task.check(&mom_file) {
+                    problems.push(mom_file.describe_task_problem(source, &name, problem));
+                }
+                flat_tasks.insert(name, task.clone());
+            }
+        }
+        // The tasks of the base files are not known without loading them
+        if mom_file.common.extend.is_empty() {
+            for error in get_missing_task_errors(&flat_tasks) {
+                problems.push(mom_file.describe_missing_task(source, error));
+            }
+        }
+        Ok(problems)
+    }
+
+    /// Returns the problems found in the `incl` templates of the mom file.
+    ///
+    /// # Arguments
+    ///
+    /// * `source`: Content of the mom file, to point to the problems
+    fn check_incl(&self, source: Option<&str>) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut tera = get_tera_instance(HashMap::new());
+        for (name, template) in &self.common.incl {
+            // Added to a copy, as Tera keeps templates that fail to extend others
+            let mut new_tera = tera.clone();
+            match new_tera.add_raw_template(&format!("incl.{name}"), template) {
+                Ok(_) => tera = new_tera,
+                Err(e) => {
+                    let description = format!(
+                        "Invalid template in `incl.{}`: {}",
+                        name,
+                        TaskError::from(e).reason()
+                    );
+                    let path = [String::from("incl"), name.clone()];
+                    let position = source.and_then(|source| find_yaml_path(source, &path));
+                    problems.push(Problem {
+                        task_name: None,
+                        description: self.with_snippet(description, source, position),
+                        kind: ProblemKind::Incl(name.clone()),
+                    });
+                }
+            }
+        }
+        problems
+    }
+
+    /// Returns the description of a problem of a task, followed by a snippet of the mom file
+    /// pointing to it, if it can be located.
+    ///
+    /// # Arguments
+    ///
+    /// * `source`: Content of the mom file
+    /// * `task_name`: Name of the task with the problem
+    /// * `problem`: Problem found in the task
+    fn describe_task_problem(
+        &self,
+        source: Option<&str>,
+        task_name: &str,
+        problem: TaskError,
+    ) -> Problem {
+        let description = format!("Task `{}`: {}", task_name, problem.reason());
+        let field = match &problem {
+            TaskError::Template { field, .. } => Some(field.as_str()),
+            _ => None,
+        };
+        let position = source.and_then(|source| find_task_position(source, task_name, field));
+        Problem {
+            task_name: Some(String::from(task_name)),
+            description: self.with_snippet(description, source, position),
+            kind: ProblemKind::Task(problem),
+        }
+    }
+
+    /// Returns the problem of a task that refers to a task that does not exist, pointing to the
+    /// task if it can be located.
+    ///
+    /// # Arguments
+    ///
+    /// * `source`: Content of the mom file
+    /// * `error`: Error of the missing task
+    fn describe_missing_task(&self, source: Option<&str>, error: MissingTaskError) -> Problem {
+        let position = source.and_then(|source| find_task_position(source, &error.task_name, None));
+        Problem {
+            task_name: Some(error.task_name.clone()),
+            description: self.with_snippet(error.to_string(), source, position),
+            kind: ProblemKind::MissingTask(error.missing_name),
+        }
+    }
+
+    /// Returns the description followed by a snippet of the mom file at the given position, if
+    /// any.
+    fn with_snippet(
+        &self,
+        description: String,
+        source: Option<&str>,
+        position: Option<Position>,
+    ) -> String {
+        match (source, position) {
+            (Some(source), Some(position)) => format!(
+                "{}\n{}",
+                description,
+                render_snippet(&self.filepath, source, position)
+            ),
+            _ => description,
+        }
     }
 
     /// Returns plain and OS specific tasks with normalized names. This consumes `self.tasks`
@@ -279,4 +614,21 @@ impl MomFile {
             .map(|t| t.get_name())
             .collect()
     }
+
+    /// Returns the list of names of tasks that are not private in the included mom files,
+    /// prefixed with their namespace, i.e. `backend:test`
+    pub(crate) fn get_included_public_task_names(&self) -> Vec<String> {
+        let mut task_names = Vec::new();
+        for (namespace, mom_file) in &self.included {
+            let names = mom_file
+                .get_public_task_names()
+                .into_iter()
+                .map(String::from)
+                .chain(mom_file.get_included_public_task_names());
+            for name in names {
+                task_names.push(format!("{}:{}", namespace, name));
+            }
+        }
+        task_names
+    }
 }
@@ -3,7 +3,6 @@
 mod mom_files_container_test;
 
 use std::{
-    collections::HashSet,
     path::PathBuf,
     sync::{Arc, Mutex},
 };
@@ -18,7 +17,8 @@ pub(crate) type MomFileSharedPtr = Arc<Mutex<MomFile>>;
 pub(crate) struct MomFilesContainer {
     /// Cached mom files
     cached: IndexMap<PathBuf, MomFileSharedPtr>,
-    loading: HashSet<PathBuf>,
+    /// Mom files being extended, in the order they started loading
+    loading: Vec<PathBuf>,
 }
 
 impl MomFilesContainer {
@@ -26,16 +26,21 @@ impl MomFilesContainer {
     pub(crate) fn new() -> Self {
         MomFilesContainer {
             cached: IndexMap::new(),
-            loading: HashSet::new(),
+            loading: Vec::new(),
         }
     }
 
     /// Just loads the mom file without extending it.
     pub(crate) fn load_mom_file(&mut self, path: PathBuf) -> DynErrResult<MomFileSharedPtr> {
-        if self.loading.contains(&path) {
+        if let Some(position) = self.loading.iter().position(|p| p == &path) {
+            let cycle: Vec<String> = self.loading[position..]
+                .iter()
+                .chain(std::iter::once(&path))
+                .map(|p| p.display().to_string())
+                .collect();
             return Err(format!(
                 "Found a cyclic dependency for mom file: {}",
-                &path.display()
+                cycle.join(" -> ")
             )
             .into());
         }
@@ -54,7 +59,8 @@ impl MomFilesContainer {
         }
     }
 
-    /// Reads the mom file from the given path.
+    /// Reads the mom file from the given path, extending it from its bases and loading the files
+    /// it includes.
     ///
     /// # Arguments
     ///
@@ -67,11 +73,11 @@ impl MomFilesContainer {
         let mut mom_file_lock = mom_file.lock().unwrap();
         let mom_file_lock = &mut *mom_file_lock;
 
-        if mom_file_lock.common.extend.is_empty() {
+        if mom_file_lock.common.extend.is_empty() && mom_file_lock.include.is_empty() {
             return Ok(Arc::clone(&mom_file));
         }
 
-        self.loading.insert(mom_file_lock.filepath.clone());
+        self.loading.push(mom_file_lock.filepath.clone());
 
         let bases = std::mem::take(&mut mom_file_lock.common.extend);
         for base in bases.iter() {
@@ -80,7 +86,20 @@ impl MomFilesContainer {
             mom_file_lock.extend(&base_mom_file.lock().unwrap());
         }
 
-        self.loading.remove(&mom_file_lock.filepath);
+        // Included after extending, so that the included files take precedence over the ones
+        // included by the bases
+        let includes = std::mem::take(&mut mom_file_lock.include);
+        for (namespace, path) in includes.iter() {
+            let full_path = get_path_relative_to_base(&mom_file_lock.directory, path);
+            let included_mom_file = self.read_mom_file(full_path)?;
+            // Copied, so that the tasks of the included file can run without locking it
+            let included_mom_file = included_mom_file.lock().unwrap().clone();
+            mom_file_lock
+                .included
+                .insert(namespace.clone(), Arc::new(included_mom_file));
+        }
+
+        self.loading.pop();
 
         Ok(Arc::clone(&mom_file))
     }
@@ -1,7 +1,8 @@
-use std::{fs::File, io::Write};
+use std::{fs, fs::File, io::Write};
 
 use assert_fs::TempDir;
 
+use crate::dynamic_values::EnvValue;
 use crate::mom_files_container::MomFilesContainer;
 
 #[test]
@@ -63,13 +64,19 @@ fn test_extend_cyclic_dependency() {
 
     let mut mom_files = MomFilesContainer::new();
 
-    let config_file = mom_files.read_mom_file(target_mom_file_path);
+    let config_file = mom_files.read_mom_file(target_mom_file_path.clone());
     assert!(config_file.is_err());
 
     let err = config_file.err().unwrap();
-    assert!(err
-        .to_string()
-        .starts_with("Found a cyclic dependency for mom file: "));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Found a cyclic dependency for mom file: {} -> {} -> {}",
+            target_mom_file_path.display(),
+            tmp_dir.path().join("mom.source.yml").display(),
+            target_mom_file_path.display()
+        )
+    );
 }
 
 #[test]
@@ -141,12 +148,130 @@ fn test_extend() {
     assert_eq!(task.script().unwrap(), "echo hello t1.1");
 
     let env = &config_file.common.env;
-    assert_eq!(env.get("EVAR1").unwrap(), "ROOT_EVAL1.1");
-    assert_eq!(env.get("EVAR2").unwrap(), "ROOT_EVAL2");
-    assert_eq!(env.get("EVAR3").unwrap(), "ROOT_EVAL3");
+    assert_eq!(env.get("EVAR1").unwrap(), &EnvValue::from("ROOT_EVAL1.1"));
+    assert_eq!(env.get("EVAR2").unwrap(), &EnvValue::from("ROOT_EVAL2"));
+    assert_eq!(env.get("EVAR3").unwrap(), &EnvValue::from("ROOT_EVAL3"));
 
     let vars = &config_file.common.vars;
     assert_eq!(vars.get("VAR1").unwrap(), "ROOT_VAL1.1");
     assert_eq!(vars.get("VAR2").unwrap(), "ROOT_VAL2");
     assert_eq!(vars.get("VAR3").unwrap(), "ROOT_VAL3");
 }
+
+#[test]
+fn test_include() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(tmp_dir.path().join("backend").join("db")).unwrap();
+    fs::write(
+        tmp_dir.path().join("mom.root.yml"),
+        r#"
+    version: 1
+
+    include:
+        backend: backend/mom.yml
+
+    tasks:
+        test:
+            script: "echo hello root"
+    "#,
+    )
+    .unwrap();
+    fs::write(
+        tmp_dir.path().join("backend").join("mom.yml"),
+        r#"
+    version: 1
+
+    include:
+        db: db/mom.yml
+
+    tasks:
+        test:
+            script: "echo hello backend"
+
+        hidden:
+            private: true
+            script: "echo hidden"
+    "#,
+    )
+    .unwrap();
+    fs::write(
+        tmp_dir.path().join("backend").join("db").join("mom.yml"),
+        r#"
+    version: 1
+
+    tasks:
+        migrate:
+            script: "echo migrate"
+    "#,
+    )
+    .unwrap();
+
+    let mut mom_files = MomFilesContainer::new();
+    let mom_file = mom_files
+        .read_mom_file(tmp_dir.path().join("mom.root.yml"))
+        .unwrap();
+    let mom_file = mom_file.lock().unwrap();
+
+    let (backend, task_name) = mom_file.resolve_namespace("backend:test").unwrap();
+    assert_eq!(task_name, "test");
+    assert_eq!(backend.directory, tmp_dir.path().join("backend"));
+    assert_eq!(
+        backend.clone_task(task_name).unwrap().script().unwrap(),
+        "echo hello backend"
+    );
+
+    let (db, task_name) = mom_file.resolve_namespace("backend:db:migrate").unwrap();
+    assert_eq!(task_name, "migrate");
+    assert!(db.has_task("migrate"));
+
+    let (root, task_name) = mom_file.resolve_namespace("test").unwrap();
+    assert_eq!(task_name, "test");
+    assert_eq!(
+        root.clone_task(task_name).unwrap().script().unwrap(),
+        "echo hello root"
+    );
+
+    assert!(mom_file.resolve_namespace("frontend:test").is_none());
+
+    let mut task_names = mom_file.get_included_public_task_names();
+    task_names.sort();
+    assert_eq!(task_names, vec!["backend:db:migrate", "backend:test"]);
+}
+
+#[test]
+fn test_include_cyclic_dependency() {
+    let tmp_dir = TempDir::new().unwrap();
+    let root_mom_file_path = tmp_dir.path().join("mom.root.yml");
+    fs::write(
+        &root_mom_file_path,
+        r#"
+    version: 1
+
+    include:
+        other: mom.other.yml
+    "#,
+    )
+    .unwrap();
+    fs::write(
+        tmp_dir.path().join("mom.other.yml"),
+        r#"
+    version: 1
+
+    include:
+        root: mom.root.yml
+    "#,
+    )
+    .unwrap();
+
+    let mut mom_files = MomFilesContainer::new();
+    let err = mom_files.read_mom_file(root_mom_file_path.clone()).err();
+    assert_eq!(
+        err.unwrap().to_string(),
+        format!(
+            "Found a cyclic dependency for mom file: {} -> {} -> {}",
+            root_mom_file_path.display(),
+            tmp_dir.path().join("mom.other.yml").display(),
+            root_mom_file_path.display()
+        )
+    );
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::dynamic_values::EnvValue;
 use assert_fs::TempDir;
 use std::fs::File;
 use std::io::Write;
@@ -51,9 +52,12 @@ tasks:
     assert!(mom_file.has_task("hello_local"));
     assert_eq!(
         mom_file.common.env.get("VALUE_OVERRIDE").unwrap(),
-        "NEW_VALUE"
+        &EnvValue::from("NEW_VALUE")
+    );
+    assert_eq!(
+        mom_file.common.env.get("OTHER_VALUE").unwrap(),
+        &EnvValue::from("HELLO")
     );
-    assert_eq!(mom_file.common.env.get("OTHER_VALUE").unwrap(), "HELLO");
 }
 
 #[test]
@@ -177,10 +181,10 @@ tasks:
 
     let err = mom_file.err().unwrap();
 
-    // Can be either task_1 or task_2
-    assert!(err
-        .to_string()
-        .starts_with("Found a cyclic dependency for task: task_"));
+    assert_eq!(
+        err.to_string(),
+        "Found a cyclic dependency for task: task_1 -> task_2 -> task_1"
+    );
 
     let mom_file = MomFile::from_str(
         r#"
@@ -203,10 +207,36 @@ tasks:
 
     let err = mom_file.err().unwrap();
 
-    // Can be either task_1 or task_2
-    assert!(err
-        .to_string()
-        .starts_with("Found a cyclic dependency for task: task_"));
+    assert_eq!(
+        err.to_string(),
+        "Found a cyclic dependency for task: task_1 -> task_2 -> task_1"
+    );
+}
+
+#[test]
+fn test_task_circular_deps_return_error() {
+    let mom_file = MomFile::from_str(
+        r#"
+version: 1
+
+tasks:
+    task_1:
+        deps: task_2
+        script: echo hello
+
+    task_2:
+        deps: [task_1]
+        script: echo hello again
+"#,
+    );
+    assert!(mom_file.is_err());
+
+    let err = mom_file.err().unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "Found a cyclic dependency for task: task_1 -> task_2 -> task_1"
+    );
 }
 
 #[test]
@@ -277,3 +307,316 @@ tasks:
     let err = mom_file.err().unwrap();
     assert!(err.to_string().contains("Invalid task name ``"));
 }
+
+#[test]
+fn test_valid_namespace() {
+    let mom_file = MomFile::from_str(
+        r#"
+version: 1
+
+include:
+    "back:end": ./backend/mom.yml
+"#,
+    );
+    assert!(mom_file.is_err());
+
+    let err = mom_file.err().unwrap();
+    assert!(err.to_string().contains("Invalid namespace `back:end`"));
+}
+
+#[test]
+fn test_namespaced_task_dependencies_are_skipped() {
+    // Namespaced tasks are resolved when they run, as the included files are not loaded yet
+    let mom_file = MomFile::from_str(
+        r#"
+version: 1
+
+tasks:
+    test:
+        cmds:
+            - task: backend:test
+"#,
+    );
+    assert!(mom_file.is_ok());
+}
+
+#[test]
+fn test_check_path() {
+    let tmp_dir = TempDir::new().unwrap();
+    let path = tmp_dir.join("mom.root.yml");
+    fs::write(
+        &path,
+        r#"
+version: 1
+
+dotenv: missing.env
+incl:
+    broken: "{% if %}"
+
+tasks:
+    both:
+        script: echo hello
+        cmds:
+            - echo hello
+    template:
+        cmds:
+            - echo {{ vars.missing_end
+        linux:
+            condition: "{{ true }}"
+            vars_file: missing.yml
+    valid:
+        cmds:
+            - echo {% include "incl.broken" %}
+"#,
+    )
+    .unwrap();
+
+    // Loading stops at the first problem, while checking reports all of them
+    assert!(MomFile::from_path(path.clone()).is_err());
+    let problems = MomFile::check_path(&path).unwrap();
+    assert_eq!(problems.len(), 5);
+    assert!(problems[0]
+        .description
+        .starts_with("Failed to read env file at"));
+    assert!(problems[1]
+        .description
+        .starts_with("Invalid template in `incl.broken`: Failed to parse"));
+    // Problems point to where they are in the file
+    assert_eq!(
+        problems[2].description,
+        format!(
+            "Task `both`: Cannot set both `cmds` and `script`.\n --> {}:9:5\n  |\n9 |     both:\n  |     ^",
+            path.display()
+        )
+    );
+    assert!(problems[3]
+        .description
+        .starts_with("Task `template`: Invalid template in `cmds.0`"));
+    assert!(problems[3]
+        .description
+        .ends_with("15 |             - echo {{ vars.missing_end\n   |               ^"));
+    assert!(problems[4]
+        .description
+        .starts_with("Task `template.linux`: Failed to read vars file at"));
+
+    fs::write(&path, "version: 1\ntasks: [").unwrap();
+    assert!(MomFile::check_path(&path).is_err());
+}
+
+#[test]
+fn test_check() {
+    let mom_file = MomFile::from_str(
+        r#"
+version: 1
+
+incl:
+    base: "{% block content %}{% endblock %}"
+
+tasks:
+    valid:
+        script_runner: python {{ script_path }}
+        script: |
+            {% extends "incl.base" %}
+            {% block content %}print("hello"){% endblock %}
+    invalid:
+        incl:
+            child: '{% extends "incl.missing" %}'
+        cmds:
+            - parallel:
+                - echo {{ args.0 }}
+                - task:
+                    cmds:
+                        - echo {{ args.0
+        finally:
+            - echo {% endif %}
+"#,
+    )
+    .unwrap();
+
+    let problems = mom_file.check();
+    assert_eq!(problems.len(), 3);
+    assert!(problems[0]
+        .description
+        .starts_with("Task `invalid`: Invalid template in `incl.child`"));
+    assert!(problems[1]
+        .description
+        .starts_with("Task `invalid`: In `cmds.0.1.task`: Invalid template in `cmds.0`"));
+    assert!(problems[2]
+        .description
+        .starts_with("Task `invalid`: Invalid template in `finally.0`"));
+}
+
+#[test]
+fn test_check_tasks_of_every_os() {
+    let mom_file = MomFile::from_str(
+        r#"
+version: 1
+
+tasks:
+    build:
+        cmds:
+            - echo building
+        linux:
+            cmds:
+                - echo {{ args.0
+        windows:
+            cmds:
+                - echo {{ args.0
+        macos:
+            cmds:
+                - echo {{ args.0
+"#,
+    )
+    .unwrap();
+
+    // Not only the ones of the current OS
+    let problems = mom_file.check();
+    assert_eq!(problems.len(), 3);
+    assert!(problems[0]
+        .description
+        .starts_with("Task `build.linux`: Invalid template in `cmds.0`"));
+    assert!(problems[1]
+        .description
+        .starts_with("Task `build.macos`: Invalid template in `cmds.0`"));
+    assert!(problems[2]
+        .description
+        .starts_with("Task `build.windows`: Invalid template in `cmds.0`"));
+}
+
+#[test]
+fn test_check_path_missing_tasks() {
+    let tmp_dir = TempDir::new().unwrap();
+    let path = tmp_dir.join("mom.root.yml");
+    fs::write(
+        &path,
+        r#"
+version: 1
+
+tasks:
+    build:
+        deps: [missing_dep, test]
+        cmds:
+            - task: missing_task
+    test:
+        extend: missing_base
+        cmds:
+            - echo testing
+"#,
+    )
+    .unwrap();
+
+    // Loading stops at the first task that is not found
+    assert!(MomFile::from_path(path.clone()).is_err());
+    let problems = MomFile::check_path(&path).unwrap();
+    assert_eq!(problems.len(), 3);
+    assert!(problems[0]
+        .description
+        .starts_with("Task build cannot inherit from non-existing task missing_dep."));
+    assert!(problems[1]
+        .description
+        .starts_with("Task build cannot inherit from non-existing task missing_task."));
+    assert!(problems[2]
+        .description
+        .starts_with("Task test cannot inherit from non-existing task missing_base."));
+    assert!(problems[2]
+        .description
+        .ends_with("9 |     test:\n  |     ^"));
+}
+
+#[test]
+fn test_problem_is_same_as_load_error() {
+    let tmp_dir = TempDir::new().unwrap();
+    let path = tmp_dir.join("mom.root.yml");
+    fs::write(
+        &path,
+        r#"
+version: 1
+
+dotenv: missing.env
+
+tasks:
+    build:
+        script: echo hello
+        cmds:
+            - echo hello
+    test:
+        deps: [missing]
+"#,
+    )
+    .unwrap();
+
+    let problems = MomFile::check_path(&path).unwrap();
+    assert_eq!(problems.len(), 3);
+    let load_error = MomFile::from_path(path.clone()).err().unwrap();
+    assert!(problems[0].is_same_as(load_error.as_ref()));
+    assert!(!problems[1].is_same_as(load_error.as_ref()));
+
+    // Problems of a task are only the same for the same task and kind
+    let error = AwareTaskError::new(
+        "build",
+        TaskError::ConfigError(String::from("Cannot set both `cmds` and `script`.")),
+    );
+    assert!(problems[1].is_same_as(&error));
+    let error = AwareTaskError::new("test", error.error.clone());
+    assert!(!problems[1].is_same_as(&error));
+    let error = MissingTaskError::new("test", "missing");
+    assert!(problems[2].is_same_as(&error));
+    assert!(!problems[1].is_same_as(&error));
+
+    // Errors checking does not find are never the same
+    let error: Box<dyn Error> = "missing field `version`".into();
+    assert!(problems
+        .iter()
+        .all(|problem| !problem.is_same_as(error.as_ref())));
+}
+
+#[test]
+fn test_load_errors_point_to_the_file() {
+    let tmp_dir = TempDir::new().unwrap();
+    let path = tmp_dir.join("mom.root.yml");
+    fs::write(&path, "version: 1\n\ntasks:\n    build: [\n").unwrap();
+    let err = MomFile::from_path(path.clone()).err().unwrap().to_string();
+    assert!(err.starts_with("tasks.build: invalid type: sequence, expected struct Task\n"));
+    assert!(err.contains(&format!(" --> {}:4:12\n", path.display())));
+
+    fs::write(
+        &path,
+        r#"
+version: 1
+
+tasks:
+    build:
+        linux:
+            program: cargo
+            cmds:
+                - cargo build
+"#,
+    )
+    .unwrap();
+    let err = MomFile::from_path(path.clone()).err().unwrap().to_string();
+    assert_eq!(
+        err,
+        format!(
+            "Task `build.linux` failed:\nImproperly configured:\nCannot set both `cmds` and `program`.\n --> {}:6:9\n  |\n6 |         linux:\n  |         ^",
+            path.display()
+        )
+    );
+
+    // Unknown fields of tasks point to where the YAML parser reports them, the start of the task
+    fs::write(
+        &path,
+        r#"
+version: 1
+
+tasks:
+    build:
+        cmds:
+            - cargo build
+        scirpt: cargo build
+"#,
+    )
+    .unwrap();
+    let err = MomFile::from_path(path.clone()).err().unwrap().to_string();
+    assert!(err.starts_with("tasks.build: unknown field `scirpt`"));
+    assert!(err.ends_with("6 |         cmds:\n  |         ^"));
+}
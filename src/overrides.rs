@@ -0,0 +1,77 @@
+#[cfg(test)]
+#[path = "overrides_test.rs"]
+mod overrides_test;
+
+use std::collections::HashMap;
+
+use crate::dynamic_values::EnvValue;
+use crate::types::DynErrResult;
+
+/// Values given with `--set` in the command line, which take precedence over the `env` and
+/// `vars` of every task that runs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Overrides {
+    /// Environment variables to override
+    pub(crate) env: HashMap<String, String>,
+    /// Variables to override
+    pub(crate) vars: HashMap<String, serde_yaml::Value>,
+}
+
+impl Overrides {
+    /// Parses the overrides from a list of `vars.KEY=VALUE` or `env.KEY=VALUE` values. The
+    /// values of the variables are parsed as YAML, so that lists and maps can be given, while
+    /// the values of the environment variables are taken as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `values`: Values to parse, where the last one takes precedence for the same key
+    pub(crate) fn parse<S: AsRef<str>>(values: &[S]) -> DynErrResult<Self> {
+        let mut overrides = Overrides::default();
+        for value in values {
+            let value = value.as_ref();
+            let (key, val) = match value.split_once('=') {
+                Some((key, val)) => (key, val),
+                None => {
+                    return Err(format!("Invalid override `{}`, expected KEY=VALUE.", value).into())
+                }
+            };
+            if let Some(name) = key.strip_prefix("env.").filter(|name| !name.is_empty()) {
+                overrides.env.insert(String::from(name), String::from(val));
+            } else if let Some(name) = key.strip_prefix("vars.").filter(|name| !name.is_empty()) {
+                // An empty YAML document is null, but an empty string is more likely expected
+                let val = match val {
+                    "" => serde_yaml::Value::String(String::new()),
+                    val => serde_yaml::from_str(val)
+                        .map_err(|e| format!("Invalid value for `{}`: {}", key, e))?,
+                };
+                overrides.vars.insert(String::from(name), val);
+            } else {
+                return Err(format!(
+                    "Invalid override `{}`, the key must start with `vars.` or `env.`.",
+                    key
+                )
+                .into());
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// Applies the overrides to the given environment variables and variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `env`: Environment variables of the task
+    /// * `vars`: Variables of the task
+    pub(crate) fn apply(
+        &self,
+        env: &mut HashMap<String, EnvValue>,
+        vars: &mut HashMap<String, serde_yaml::Value>,
+    ) {
+        for (key, val) in &self.env {
+            env.insert(key.clone(), EnvValue::from(val.as_str()));
+        }
+        for (key, val) in &self.vars {
+            vars.insert(key.clone(), val.clone());
+        }
+    }
+}
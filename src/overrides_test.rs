@@ -0,0 +1,61 @@
+use super::*;
+
+#[test]
+fn test_parse_overrides() {
+    let overrides = Overrides::parse(&[
+        "vars.name=world",
+        "vars.list=[1, 2]",
+        "vars.map={a: b}",
+        "vars.empty=",
+        "env.PATH_VAR=a=b",
+        "vars.name=again",
+    ])
+    .unwrap();
+
+    assert_eq!(overrides.env.len(), 1);
+    assert_eq!(overrides.env.get("PATH_VAR").unwrap(), "a=b");
+
+    assert_eq!(overrides.vars.len(), 4);
+    assert_eq!(
+        overrides.vars.get("name").unwrap(),
+        &serde_yaml::Value::from("again")
+    );
+    assert_eq!(
+        overrides.vars.get("list").unwrap(),
+        &serde_yaml::from_str::<serde_yaml::Value>("[1, 2]").unwrap()
+    );
+    assert_eq!(
+        overrides.vars.get("map").unwrap(),
+        &serde_yaml::from_str::<serde_yaml::Value>("a: b").unwrap()
+    );
+    assert_eq!(
+        overrides.vars.get("empty").unwrap(),
+        &serde_yaml::Value::from("")
+    );
+}
+
+#[test]
+fn test_parse_invalid_overrides() {
+    let err = Overrides::parse(&["vars.name"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid override `vars.name`, expected KEY=VALUE."
+    );
+
+    let err = Overrides::parse(&["name=value"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid override `name`, the key must start with `vars.` or `env.`."
+    );
+
+    let err = Overrides::parse(&["env.=value"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid override `env.`, the key must start with `vars.` or `env.`."
+    );
+
+    let err = Overrides::parse(&["vars.list=[1, 2"]).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("Invalid value for `vars.list`: "));
+}
@@ -0,0 +1,298 @@
+#[cfg(test)]
+#[path = "params_test.rs"]
+mod params_test;
+
+use std::collections::HashMap;
+use std::env::current_dir;
+use std::fmt;
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::args::ArgsContext;
+use crate::defaults::default_false;
+use crate::errors::TaskError;
+use crate::utils::get_path_relative_to_base;
+
+/// Type of a task parameter
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ParamType {
+    #[default]
+    String,
+    Int,
+    /// Flag, given as `--name`, or explicitly as `--name=true` or `--name=false`
+    Bool,
+    /// One of the values given in `choices`
+    Enum,
+    /// Path, made absolute relative to the current directory, or to the mom file directory for
+    /// default values
+    Path,
+}
+
+impl fmt::Display for ParamType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParamType::String => write!(f, "string"),
+            ParamType::Int => write!(f, "int"),
+            ParamType::Bool => write!(f, "bool"),
+            ParamType::Enum => write!(f, "enum"),
+            ParamType::Path => write!(f, "path"),
+        }
+    }
+}
+
+/// Parameter accepted by a task
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Param {
+    /// Name of the parameter, given as `--name` in the command line and available as
+    /// `params.name` in the templates
+    pub(crate) name: String,
+
+    /// Type the value is converted to
+    #[serde(default, rename = "type")]
+    pub(crate) param_type: ParamType,
+
+    /// Help of the parameter
+    pub(crate) help: Option<String>,
+
+    /// Value to use if the parameter is not given
+    pub(crate) default: Option<serde_yaml::Value>,
+
+    /// Whether the parameter must be given
+    #[serde(default = "default_false")]
+    pub(crate) required: bool,
+
+    /// Whether the value is taken from the positional arguments, in the order the positional
+    /// parameters are declared
+    #[serde(default = "default_false")]
+    pub(crate) positional: bool,
+
+    /// Allowed values of enum parameters
+    #[serde(default)]
+    pub(crate) choices: Vec<String>,
+}
+
+impl Param {
+    /// Validates the declaration of the parameter.
+    pub(crate) fn validate(&self) -> Result<(), TaskError> {
+        lazy_static! {
+            // Same as the keys accepted by `ArgsContext`
+            static ref PARAM_NAME_REGEX: Regex = Regex::new(r"^[a-zA-Z]+\w*$").unwrap();
+        }
+        if !PARAM_NAME_REGEX.is_match(&self.name) {
+            return Err(TaskError::ConfigError(format!(
+                "Invalid parameter name `{}`.",
+                self.name
+            )));
+        }
+        if self.param_type == ParamType::Enum && self.choices.is_empty() {
+            return Err(TaskError::ConfigError(format!(
+                "Parameter `{}` of type enum must have `choices`.",
+                self.name
+            )));
+        }
+        if self.param_type != ParamType::Enum && !self.choices.is_empty() {
+            return Err(TaskError::ConfigError(format!(
+                "Parameter `{}` cannot have `choices` unless it is of type enum.",
+                self.name
+            )));
+        }
+        if self.param_type == ParamType::Bool && self.positional {
+            return Err(TaskError::ConfigError(format!(
+                "Parameter `{}` of type bool cannot be positional.",
+                self.name
+            )));
+        }
+        if let Some(default) = self.get_default()? {
+            if self.required {
+                return Err(TaskError::ConfigError(format!(
+                    "Parameter `{}` cannot be required and have a default.",
+                    self.name
+                )));
+            }
+            self.parse_value(&default, Path::new(""))
+                .map_err(|expected| {
+                    TaskError::ConfigError(format!(
+                        "Invalid default `{}` for parameter `{}`, expected {}.",
+                        default, self.name, expected
+                    ))
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Returns the default value as a string, if any.
+    fn get_default(&self) -> Result<Option<String>, TaskError> {
+        match &self.default {
+            None | Some(serde_yaml::Value::Null) => Ok(None),
+            Some(serde_yaml::Value::String(val)) => Ok(Some(val.clone())),
+            Some(serde_yaml::Value::Number(val)) => Ok(Some(val.to_string())),
+            Some(serde_yaml::Value::Bool(val)) => Ok(Some(val.to_string())),
+            Some(_) => Err(TaskError::ConfigError(format!(
+                "The default of parameter `{}` must be a string, number or boolean.",
+                self.name
+            ))),
+        }
+    }
+
+    /// Returns the value the parameter takes when it is not given: its default, or null if it
+    /// does not have one, except for bool parameters, which are false.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_path`: Path the relative default paths are resolved against
+    pub(crate) fn get_default_value(&self, base_path: &Path) -> Result<tera::Value, TaskError> {
+        // Validated when loading the task, so this should not fail
+        match self.get_default()? {
+            Some(default) => self
+                .parse_value(&default, base_path)
+                .map_err(TaskError::ConfigError),
+            None if self.param_type == ParamType::Bool => Ok(tera::Value::Bool(false)),
+            None => Ok(tera::Value::Null),
+        }
+    }
+
+    /// Converts the given value to the type of the parameter. On error, returns a description
+    /// of the expected value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: Value to convert
+    /// * `base_path`: Path relative paths are resolved against
+    fn parse_value(&self, value: &str, base_path: &Path) -> Result<tera::Value, String> {
+        match self.param_type {
+            ParamType::String => Ok(tera::Value::from(value)),
+            ParamType::Int => value
+                .parse::<i64>()
+                .map(tera::Value::from)
+                .map_err(|_| String::from("an integer")),
+            ParamType::Bool => match value.to_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok(tera::Value::Bool(true)),
+                "false" | "no" | "0" => Ok(tera::Value::Bool(false)),
+                _ => Err(String::from("true or false")),
+            },
+            ParamType::Enum => {
+                if self.choices.iter().any(|choice| choice == value) {
+                    Ok(tera::Value::from(value))
+                } else {
+                    Err(format!("one of: {}", self.choices.join(", ")))
+                }
+            }
+            ParamType::Path => {
+                let path = get_path_relative_to_base(base_path, value);
+                Ok(tera::Value::from(path.to_string_lossy()))
+            }
+        }
+    }
+
+    /// Returns how the parameter is given in the command line, i.e. `--name <int>`
+    pub(crate) fn get_usage(&self) -> String {
+        let value = match self.param_type {
+            ParamType::Enum => format!("<{}>", self.choices.join("|")),
+            param_type => format!("<{}>", param_type),
+        };
+        if self.positional {
+            format!("<{}>", self.name)
+        } else if self.param_type == ParamType::Bool {
+            format!("--{}", self.name)
+        } else {
+            format!("--{} {}", self.name, value)
+        }
+    }
+}
+
+/// Returns the values of the given parameters, taken from the arguments the task was called
+/// with and converted to their types. Parameters that are not given take their default, or
+/// null if they do not have one, except bool parameters, which are false. If any parameters
+/// are declared, the arguments that do not match one of them are rejected.
+///
+/// # Arguments
+///
+/// * `params`: Parameters of the task
+/// * `args`: Arguments the task was called with
+/// * `base_path`: Path the relative default paths are resolved against
+pub(crate) fn get_param_values(
+    params: &[Param],
+    args: &ArgsContext,
+    base_path: &Path,
+) -> Result<HashMap<String, tera::Value>, TaskError> {
+    if params.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    // The args are parsed again, as bool parameters do not take a value, unlike the kwargs
+    let mut named: HashMap<String, String> = HashMap::new();
+    let mut positional: Vec<&str> = Vec::new();
+    let get_param = |key: &str| {
+        params
+            .iter()
+            .find(|param| param.name == key)
+            .ok_or_else(|| TaskError::InvalidArgs(format!("Unknown parameter `{}`.", key)))
+    };
+    let mut args_iter = args.args.iter();
+    while let Some(arg) = args_iter.next() {
+        if !arg.starts_with('-') {
+            positional.push(arg);
+        } else if let Some((key, val)) = ArgsContext::get_kwarg(arg) {
+            get_param(&key)?;
+            named.insert(key, val);
+        } else if let Some(key) = ArgsContext::get_kwarg_key(arg) {
+            if get_param(&key)?.param_type == ParamType::Bool {
+                named.insert(key, String::from("true"));
+                continue;
+            }
+            match args_iter.next() {
+                Some(val) => {
+                    named.insert(key, val.clone());
+                }
+                None => {
+                    return Err(TaskError::InvalidArgs(format!(
+                        "Missing value for parameter `{}`.",
+                        key
+                    )));
+                }
+            }
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let current_dir = current_dir()?;
+    let mut positional = positional.into_iter();
+    let mut values = HashMap::new();
+    for param in params {
+        let given = match param.positional {
+            true => positional
+                .next()
+                .or(named.get(&param.name).map(String::as_str)),
+            false => named.get(&param.name).map(String::as_str),
+        };
+        let value = match given {
+            Some(given) => param.parse_value(given, &current_dir).map_err(|expected| {
+                TaskError::InvalidArgs(format!(
+                    "Invalid value `{}` for parameter `{}`, expected {}.",
+                    given, param.name, expected
+                ))
+            })?,
+            None if param.required => {
+                return Err(TaskError::InvalidArgs(format!(
+                    "Missing required parameter `{}`.",
+                    param.name
+                )));
+            }
+            None => param.get_default_value(base_path)?,
+        };
+        values.insert(param.name.clone(), value);
+    }
+    if let Some(arg) = positional.next() {
+        return Err(TaskError::InvalidArgs(format!(
+            "Unexpected argument `{}`.",
+            arg
+        )));
+    }
+    Ok(values)
+}
@@ -0,0 +1,198 @@
+use super::*;
+
+fn get_params(yaml: &str) -> Vec<Param> {
+    let params: Vec<Param> = serde_yaml::from_str(yaml).unwrap();
+    for param in &params {
+        param.validate().unwrap();
+    }
+    params
+}
+
+fn get_args(args: &[&str]) -> ArgsContext {
+    let mut context = ArgsContext::new();
+    context.args = args.iter().map(|arg| String::from(*arg)).collect();
+    context
+}
+
+#[test]
+fn test_get_param_values() {
+    let params = get_params(
+        r#"
+- name: file
+  positional: true
+- name: mode
+  type: enum
+  choices: [debug, release]
+  default: debug
+- name: jobs
+  type: int
+  default: 2
+- name: verbose
+  type: bool
+- name: output
+  type: path
+  default: out
+- name: tag
+"#,
+    );
+
+    let values = get_param_values(&params, &get_args(&[]), Path::new("/base")).unwrap();
+    assert_eq!(values.get("file").unwrap(), &tera::Value::Null);
+    assert_eq!(values.get("mode").unwrap(), &tera::Value::from("debug"));
+    assert_eq!(values.get("jobs").unwrap(), &tera::Value::from(2));
+    assert_eq!(values.get("verbose").unwrap(), &tera::Value::Bool(false));
+    assert_eq!(
+        values.get("output").unwrap(),
+        &tera::Value::from(Path::new("/base").join("out").to_string_lossy())
+    );
+    assert_eq!(values.get("tag").unwrap(), &tera::Value::Null);
+
+    let args = get_args(&[
+        "--verbose",
+        "--mode",
+        "release",
+        "main.rs",
+        "--jobs=4",
+        "--tag",
+        "--latest",
+    ]);
+    let values = get_param_values(&params, &args, Path::new("/base")).unwrap();
+    assert_eq!(values.get("file").unwrap(), &tera::Value::from("main.rs"));
+    assert_eq!(values.get("mode").unwrap(), &tera::Value::from("release"));
+    assert_eq!(values.get("jobs").unwrap(), &tera::Value::from(4));
+    assert_eq!(values.get("verbose").unwrap(), &tera::Value::Bool(true));
+    assert_eq!(values.get("tag").unwrap(), &tera::Value::from("--latest"));
+}
+
+#[test]
+fn test_get_param_values_errors() {
+    let params = get_params(
+        r#"
+- name: file
+  positional: true
+  required: true
+- name: mode
+  type: enum
+  choices: [debug, release]
+- name: jobs
+  type: int
+"#,
+    );
+
+    let err = get_param_values(&params, &get_args(&[]), Path::new("")).unwrap_err();
+    assert_eq!(
+        err,
+        TaskError::InvalidArgs(String::from("Missing required parameter `file`."))
+    );
+
+    let args = get_args(&["main.rs", "--mode", "fast"]);
+    let err = get_param_values(&params, &args, Path::new("")).unwrap_err();
+    assert_eq!(
+        err,
+        TaskError::InvalidArgs(String::from(
+            "Invalid value `fast` for parameter `mode`, expected one of: debug, release."
+        ))
+    );
+
+    let args = get_args(&["main.rs", "--jobs=many"]);
+    let err = get_param_values(&params, &args, Path::new("")).unwrap_err();
+    assert_eq!(
+        err,
+        TaskError::InvalidArgs(String::from(
+            "Invalid value `many` for parameter `jobs`, expected an integer."
+        ))
+    );
+
+    let args = get_args(&["main.rs", "--jobs"]);
+    let err = get_param_values(&params, &args, Path::new("")).unwrap_err();
+    assert_eq!(
+        err,
+        TaskError::InvalidArgs(String::from("Missing value for parameter `jobs`."))
+    );
+
+    let args = get_args(&["main.rs", "--force"]);
+    let err = get_param_values(&params, &args, Path::new("")).unwrap_err();
+    assert_eq!(
+        err,
+        TaskError::InvalidArgs(String::from("Unknown parameter `force`."))
+    );
+
+    let args = get_args(&["main.rs", "--force=yes"]);
+    let err = get_param_values(&params, &args, Path::new("")).unwrap_err();
+    assert_eq!(
+        err,
+        TaskError::InvalidArgs(String::from("Unknown parameter `force`."))
+    );
+
+    let args = get_args(&["main.rs", "lib.rs"]);
+    let err = get_param_values(&params, &args, Path::new("")).unwrap_err();
+    assert_eq!(
+        err,
+        TaskError::InvalidArgs(String::from("Unexpected argument `lib.rs`."))
+    );
+}
+
+#[test]
+fn test_validate_params() {
+    let cases = [
+        ("name: 1file", "Invalid parameter name `1file`."),
+        (
+            "{name: mode, type: enum}",
+            "Parameter `mode` of type enum must have `choices`.",
+        ),
+        (
+            "{name: mode, choices: [a]}",
+            "Parameter `mode` cannot have `choices` unless it is of type enum.",
+        ),
+        (
+            "{name: flag, type: bool, positional: true}",
+            "Parameter `flag` of type bool cannot be positional.",
+        ),
+        (
+            "{name: jobs, type: int, default: many}",
+            "Invalid default `many` for parameter `jobs`, expected an integer.",
+        ),
+        (
+            "{name: jobs, required: true, default: 1}",
+            "Parameter `jobs` cannot be required and have a default.",
+        ),
+        (
+            "{name: jobs, default: [1]}",
+            "The default of parameter `jobs` must be a string, number or boolean.",
+        ),
+    ];
+    for (yaml, expected) in cases {
+        let param: Param = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            param.validate().unwrap_err(),
+            TaskError::ConfigError(String::from(expected))
+        );
+    }
+}
+
+#[test]
+fn test_param_usage() {
+    let params = get_params(
+        r#"
+- name: file
+  positional: true
+- name: mode
+  type: enum
+  choices: [debug, release]
+- name: jobs
+  type: int
+- name: verbose
+  type: bool
+"#,
+    );
+    let usages: Vec<String> = params.iter().map(Param::get_usage).collect();
+    assert_eq!(
+        usages,
+        [
+            "<file>",
+            "--mode <debug|release>",
+            "--jobs <int>",
+            "--verbose"
+        ]
+    );
+}
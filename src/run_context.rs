@@ -0,0 +1,317 @@
+#[cfg(test)]
+#[path = "run_context_test.rs"]
+mod run_context_test;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::command_options::DurationValue;
+use crate::errors::{AwareTaskError, TaskError};
+use crate::overrides::Overrides;
+
+/// Result of a task that should only run once per invocation.
+type OnceResult = Arc<OnceLock<Result<(), AwareTaskError>>>;
+
+/// Value computed once per invocation.
+type CachedValue = Arc<OnceLock<Result<String, TaskError>>>;
+
+/// How often to check if a context was cancelled while sleeping.
+const SLEEP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Number of times the user pressed Ctrl-C. The running children receive the signal directly
+/// from the terminal, this is only used to stop mom from starting new commands.
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets a Ctrl-C handler that lets the children handle the signal, instead of dropping the parent
+/// and leaving the children running. Only the first call sets the handler.
+pub(crate) fn set_interrupt_handler() {
+    ctrlc::set_handler(|| {
+        INTERRUPTS.fetch_add(1, Ordering::SeqCst);
+    })
+    .unwrap_or(());
+}
+
+/// Time by which a task or command with a timeout must finish.
+#[derive(Debug, Clone)]
+pub(crate) struct Deadline {
+    /// Name of the task or command the timeout was set for
+    name: String,
+    /// Timeout that was set
+    timeout: DurationValue,
+    /// Instant the timeout expires
+    expires_at: Instant,
+}
+
+impl Deadline {
+    /// Returns whether the timeout expired.
+    pub(crate) fn has_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+
+    /// Returns the error of the task or command not finishing before the timeout.
+    pub(crate) fn error(&self) -> TaskError {
+        TaskError::Timeout(self.name.clone(), self.timeout)
+    }
+}
+
+/// State shared by all the tasks and commands run in a single invocation.
+#[derive(Debug, Clone)]
+pub(crate) struct RunContext {
+    /// Whether to print the commands without executing them
+    pub(crate) dry_run: bool,
+    /// Whether to spawn the children in their own process group, so that their whole process
+    /// tree can be killed when cancelled. Ctrl-C must be forwarded to them in this case, and they
+    /// cannot read from the terminal in Unix.
+    pub(crate) isolated: bool,
+    /// Values given in the command line that override the env and vars of every task
+    pub(crate) overrides: Arc<Overrides>,
+    /// Cancellation flags of this context and its parents. Running children are killed once any
+    /// of them is set.
+    cancel_flags: Vec<Arc<AtomicBool>>,
+    /// Number of Ctrl-C presses to ignore, the ones that happened before the context was shielded
+    ignored_interrupts: usize,
+    /// Earliest timeout set for this context or its parents. Running children are terminated
+    /// once it expires.
+    pub(crate) deadline: Option<Deadline>,
+    /// Results of the tasks that already ran as a dependency, by key
+    completed: Arc<Mutex<HashMap<String, OnceResult>>>,
+    /// Values of the dynamic vars and env that were already computed, by key
+    cached_values: Arc<Mutex<HashMap<String, CachedValue>>>,
+}
+
+impl RunContext {
+    /// Creates a new RunContext
+    ///
+    /// # Arguments
+    ///
+    /// * `dry_run`: Whether to print the commands without executing them
+    pub(crate) fn new(dry_run: bool) -> Self {
+        RunContext {
+            dry_run,
+            isolated: false,
+            overrides: Arc::new(Overrides::default()),
+            cancel_flags: vec![Arc::new(AtomicBool::new(false))],
+            ignored_interrupts: 0,
+            deadline: None,
+            completed: Arc::new(Mutex::new(HashMap::new())),
+            cached_values: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sets the values that override the env and vars of every task.
+    ///
+    /// # Arguments
+    ///
+    /// * `overrides`: Values to override
+    pub(crate) fn with_overrides(mut self, overrides: Overrides) -> Self {
+        self.overrides = Arc::new(overrides);
+        self
+    }
+
+    /// Returns a new context with the same options as this one, but without its state, that is,
+    /// it is not cancelled, and the tasks that already ran and the dynamic values that were
+    /// already computed will run again.
+    pub(crate) fn renew(&self) -> RunContext {
+        RunContext {
+            overrides: Arc::clone(&self.overrides),
+            ..RunContext::new(self.dry_run)
+        }
+    }
+
+    /// Returns a copy of this context that can be cancelled without cancelling this one.
+    /// Cancelling this context still cancels the returned one. The children spawned with the
+    /// returned context run isolated in their own process group.
+    pub(crate) fn child(&self) -> RunContext {
+        let mut child = self.clone();
+        child.isolated = true;
+        child.cancel_flags.push(Arc::new(AtomicBool::new(false)));
+        child
+    }
+
+    /// Returns a copy of this context that is not cancelled along with this one, and ignores
+    /// the Ctrl-C presses that happened so far, as well as its timeout, so that cleanup commands
+    /// can still run.
+    pub(crate) fn shielded(&self) -> RunContext {
+        let mut shielded = self.clone();
+        shielded.cancel_flags = vec![Arc::new(AtomicBool::new(false))];
+        shielded.ignored_interrupts = INTERRUPTS.load(Ordering::SeqCst);
+        shielded.deadline = None;
+        shielded
+    }
+
+    /// Returns a copy of this context that expires after the given timeout, unless this context
+    /// expires earlier.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: Name of the task or command the timeout is set for
+    /// * `timeout`: Time the task or command can run for
+    pub(crate) fn with_timeout(&self, name: &str, timeout: DurationValue) -> RunContext {
+        let mut ctx = self.clone();
+        let deadline = Deadline {
+            name: String::from(name),
+            timeout,
+            // Saturates to a deadline that never expires for huge timeouts
+            expires_at: Instant::now()
+                .checked_add(timeout.0)
+                .unwrap_or_else(|| Instant::now() + Duration::from_secs(u32::MAX as u64)),
+        };
+        match &self.deadline {
+            Some(current) if current.expires_at <= deadline.expires_at => {}
+            _ => ctx.deadline = Some(deadline),
+        }
+        ctx
+    }
+
+    /// Cancels this context and its children, killing the commands they are running.
+    pub(crate) fn cancel(&self) {
+        if let Some(flag) = self.cancel_flags.last() {
+            flag.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Returns whether this context or any of its parents were cancelled.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel_flags
+            .iter()
+            .any(|flag| flag.load(Ordering::SeqCst))
+    }
+
+    /// Returns whether the user pressed Ctrl-C, not counting the presses ignored by this context.
+    pub(crate) fn is_interrupted(&self) -> bool {
+        INTERRUPTS.load(Ordering::SeqCst) > self.ignored_interrupts
+    }
+
+    /// Returns an error if new commands should not be started, either because the context was
+    /// cancelled, its timeout expired or the user pressed Ctrl-C.
+    pub(crate) fn check_can_continue(&self) -> Result<(), TaskError> {
+        if self.is_interrupted() {
+            return Err(TaskError::RuntimeError(String::from("Interrupted")));
+        }
+        if self.is_cancelled() {
+            return Err(TaskError::RuntimeError(String::from("Cancelled")));
+        }
+        match &self.deadline {
+            Some(deadline) if deadline.has_expired() => Err(deadline.error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Waits for the given time, returning early with an error if the context is cancelled or
+    /// the user presses Ctrl-C.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration`: Time to wait
+    pub(crate) fn sleep(&self, duration: Duration) -> Result<(), TaskError> {
+        let start = Instant::now();
+        loop {
+            self.check_can_continue()?;
+            let elapsed = start.elapsed();
+            if elapsed >= duration {
+                return Ok(());
+            }
+            thread::sleep((duration - elapsed).min(SLEEP_POLL_INTERVAL));
+        }
+    }
+
+    /// Calls `run` only the first time it is called with the given key, returning the same
+    /// result in the following calls. If another thread is running it, waits until it finishes.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: Unique identifier of what runs
+    /// * `run`: Function to call
+    pub(crate) fn run_once<F>(&self, key: &str, run: F) -> Result<(), AwareTaskError>
+    where
+        F: FnOnce() -> Result<(), AwareTaskError>,
+    {
+        let result = {
+            let mut completed = self.completed.lock().unwrap();
+            Arc::clone(completed.entry(key.to_string()).or_default())
+        };
+        result.get_or_init(run).clone()
+    }
+
+    /// Calls `compute` only the first time it is called with the given key, returning the same
+    /// value in the following calls. If another thread is computing it, waits until it finishes.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: Unique identifier of the value
+    /// * `compute`: Function that computes the value
+    pub(crate) fn cached_value<F>(&self, key: &str, compute: F) -> Result<String, TaskError>
+    where
+        F: FnOnce() -> Result<String, TaskError>,
+    {
+        let value = {
+            let mut cached_values = self.cached_values.lock().unwrap();
+            Arc::clone(cached_values.entry(key.to_string()).or_default())
+        };
+        value.get_or_init(compute).clone()
+    }
+
+    /// Calls `run` for each index in `0..count` concurrently, with at most `jobs` calls running
+    /// at the same time. If `fail_fast` is set, the remaining calls are cancelled as soon as one
+    /// fails, otherwise all of them run and the errors are reported together.
+    ///
+    /// # Arguments
+    ///
+    /// * `count`: Number of calls
+    /// * `jobs`: Maximum number of concurrent calls
+    /// * `fail_fast`: Whether to cancel the remaining calls when one fails
+    /// * `run`: Function to call with the index and the context to run with
+    pub(crate) fn run_parallel<F>(
+        &self,
+        count: usize,
+        jobs: usize,
+        fail_fast: bool,
+        run: F,
+    ) -> Result<(), TaskError>
+    where
+        F: Fn(usize, &RunContext) -> Result<(), TaskError> + Sync,
+    {
+        if count == 0 {
+            return Ok(());
+        }
+        let group_ctx = self.child();
+        let next = AtomicUsize::new(0);
+        let errors: Mutex<Vec<(usize, TaskError)>> = Mutex::new(Vec::new());
+
+        thread::scope(|scope| {
+            for _ in 0..jobs.clamp(1, count) {
+                scope.spawn(|| {
+                    while group_ctx.check_can_continue().is_ok() {
+                        let i = next.fetch_add(1, Ordering::SeqCst);
+                        if i >= count {
+                            break;
+                        }
+                        if let Err(e) = run(i, &group_ctx) {
+                            // Stored before cancelling, so that the first error is the one that
+                            // caused the cancellation
+                            errors.lock().unwrap().push((i, e));
+                            if fail_fast {
+                                group_ctx.cancel();
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut errors = errors.into_inner().unwrap();
+        if errors.is_empty() {
+            return self.check_can_continue();
+        }
+        if fail_fast || errors.len() == 1 {
+            return Err(errors.swap_remove(0).1);
+        }
+        errors.sort_by_key(|(i, _)| *i);
+        Err(TaskError::Parallel(
+            errors.into_iter().map(|(_, e)| e).collect(),
+        ))
+    }
+}
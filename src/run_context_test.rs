@@ -0,0 +1,107 @@
+use super::*;
+
+#[test]
+fn test_cancel_child_context() {
+    let ctx = RunContext::new(false);
+    let child = ctx.child();
+    let sibling = ctx.child();
+
+    child.cancel();
+    assert!(child.is_cancelled());
+    assert!(!sibling.is_cancelled());
+    assert!(!ctx.is_cancelled());
+    assert!(child.check_can_continue().is_err());
+    assert!(sibling.check_can_continue().is_ok());
+}
+
+#[test]
+fn test_cancel_parent_context() {
+    let ctx = RunContext::new(false);
+    let child = ctx.child();
+    let grandchild = child.child();
+
+    ctx.cancel();
+    assert!(ctx.is_cancelled());
+    assert!(child.is_cancelled());
+    assert!(grandchild.is_cancelled());
+    assert_eq!(
+        grandchild.check_can_continue(),
+        Err(TaskError::RuntimeError(String::from("Cancelled")))
+    );
+}
+
+#[test]
+fn test_shielded_context() {
+    let ctx = RunContext::new(false);
+    let child = ctx.child();
+    child.cancel();
+
+    let shielded = child.shielded();
+    assert!(!shielded.is_cancelled());
+    assert!(shielded.isolated);
+    assert!(shielded.check_can_continue().is_ok());
+
+    // Cancelling the shielded context does not affect the original one
+    shielded.cancel();
+    assert!(shielded.is_cancelled());
+    assert!(!ctx.is_cancelled());
+}
+
+#[test]
+fn test_sleep() {
+    let ctx = RunContext::new(false);
+    let start = Instant::now();
+    ctx.sleep(Duration::from_millis(30)).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(30));
+
+    // Returns immediately once cancelled
+    ctx.cancel();
+    let start = Instant::now();
+    assert!(ctx.sleep(Duration::from_secs(60)).is_err());
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_context_timeout() {
+    let ctx = RunContext::new(false);
+    let task_ctx = ctx.with_timeout("task", DurationValue(Duration::from_millis(20)));
+    // The earliest deadline is kept
+    let cmd_ctx = task_ctx.with_timeout("task.cmds.0", DurationValue(Duration::from_secs(60)));
+    assert!(cmd_ctx.check_can_continue().is_ok());
+
+    thread::sleep(Duration::from_millis(30));
+    assert_eq!(
+        cmd_ctx.check_can_continue().unwrap_err(),
+        TaskError::Timeout(
+            String::from("task"),
+            DurationValue(Duration::from_millis(20))
+        )
+    );
+    assert!(ctx.check_can_continue().is_ok());
+    assert!(cmd_ctx.shielded().check_can_continue().is_ok());
+}
+
+#[test]
+fn test_cached_value() {
+    let ctx = RunContext::new(false);
+    let child = ctx.child();
+    let mut calls = 0;
+    let mut compute = || {
+        calls += 1;
+        Ok(String::from("value"))
+    };
+
+    assert_eq!(
+        ctx.cached_value("key", &mut compute),
+        Ok(String::from("value"))
+    );
+    assert_eq!(
+        child.cached_value("key", &mut compute),
+        Ok(String::from("value"))
+    );
+    assert_eq!(
+        ctx.renew().cached_value("key", &mut compute),
+        Ok(String::from("value"))
+    );
+    assert_eq!(calls, 2);
+}
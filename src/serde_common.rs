@@ -1,16 +1,18 @@
 use std::{
-    collections::{BTreeMap, HashMap},
-    mem,
-    path::Path,
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    fmt, mem,
+    path::{Path, PathBuf},
 };
 
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 
 use crate::{
+    dynamic_values::EnvValue,
+    errors::ReadFileError,
     inherit_option_value, merge_map_values,
     types::DynErrResult,
-    utils::{get_path_relative_to_base, read_env_file},
+    utils::{get_path_relative_to_base, read_env_file, read_vars_file},
 };
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -32,7 +34,7 @@ pub(crate) struct StringOrVecStringIter<'a> {
 }
 
 impl StringOrVecString {
-    pub(crate) fn iter(&self) -> StringOrVecStringIter {
+    pub(crate) fn iter(&self) -> StringOrVecStringIter<'_> {
         StringOrVecStringIter {
             task_extend: self,
             index: 0,
@@ -73,6 +75,62 @@ impl<'a> Iterator for StringOrVecStringIter<'a> {
     }
 }
 
+/// Where a value of the `env` or `vars` of a task or mom file comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Origin {
+    /// Given in the `env` or `vars` of the task or mom file itself
+    Declared,
+    /// Loaded from the given dotenv file
+    Dotenv(PathBuf),
+    /// Loaded from the given vars file
+    VarsFile(PathBuf),
+    /// Inherited from the given base task, with the origin it has there
+    BaseTask(String, Box<Origin>),
+    /// Inherited from the given base mom file, with the origin it has there
+    BaseFile(PathBuf, Box<Origin>),
+    /// Inherited from the task that called this one from its `cmds`
+    ParentTask(String, Box<Origin>),
+    /// Inherited from the mom file the task belongs to
+    File(PathBuf, Box<Origin>),
+    /// Given with `--set` in the command line
+    Override,
+}
+
+impl Origin {
+    /// Returns whether the value was given in the `env` or `vars` of a task or mom file, rather
+    /// than loaded from a file or given in the command line, even if it was inherited.
+    pub(crate) fn is_declared(&self) -> bool {
+        match self {
+            Origin::Declared => true,
+            Origin::Dotenv(_) | Origin::VarsFile(_) | Origin::Override => false,
+            Origin::BaseTask(_, origin)
+            | Origin::BaseFile(_, origin)
+            | Origin::ParentTask(_, origin)
+            | Origin::File(_, origin) => origin.is_declared(),
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (source, origin) = match self {
+            Origin::Declared => return write!(f, "task"),
+            Origin::Dotenv(path) => return write!(f, "dotenv {}", path.display()),
+            Origin::VarsFile(path) => return write!(f, "vars_file {}", path.display()),
+            Origin::Override => return write!(f, "--set"),
+            Origin::BaseTask(name, origin) => (format!("base task `{}`", name), origin),
+            Origin::BaseFile(path, origin) => (format!("base file {}", path.display()), origin),
+            Origin::ParentTask(name, origin) => (format!("parent task `{}`", name), origin),
+            Origin::File(path, origin) => (format!("file {}", path.display()), origin),
+        };
+        match origin.as_ref() {
+            // Declared where it was inherited from
+            Origin::Declared => write!(f, "{}", source),
+            origin => write!(f, "{}, {}", source, origin),
+        }
+    }
+}
+
 /// Common fields for tasks and files
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
 #[serde(deny_unknown_fields)]
@@ -81,18 +139,24 @@ pub(crate) struct CommonFields {
     #[serde(default)]
     pub(crate) wd: Option<String>,
 
-    /// Env variables for all the tasks.
+    /// Env variables for all the tasks. A value can be computed from the output of a shell
+    /// command with `{sh: "command"}`.
     #[serde(default)]
-    pub(crate) env: HashMap<String, String>,
+    pub(crate) env: HashMap<String, EnvValue>,
 
     /// Env files to read environment variables from
     #[serde(default)]
     pub(crate) dotenv: StringOrVecString,
 
-    /// Variables to be used around in the mom file
+    /// Variables to be used around in the mom file. A value can be computed from the output of
+    /// a shell command with `{sh: "command"}`.
     #[serde(default)]
     pub(crate) vars: HashMap<String, Value>,
 
+    /// YAML, JSON or TOML files to read variables from
+    #[serde(default)]
+    pub(crate) vars_file: StringOrVecString,
+
     /// Adds the given text to Tera, so that they can be included in templates
     #[serde(default)]
     pub(crate) incl: BTreeMap<String, String>, // Order matters, so we use a BTreeMap
@@ -100,19 +164,127 @@ pub(crate) struct CommonFields {
     /// Files to extend from
     #[serde(default)]
     pub(crate) extend: StringOrVecString,
+
+    /// Origin of the env values that were not given in `env`
+    #[serde(skip)]
+    pub(crate) env_origins: HashMap<String, Origin>,
+
+    /// Origin of the variables that were not given in `vars`
+    #[serde(skip)]
+    pub(crate) vars_origins: HashMap<String, Origin>,
 }
 
 impl CommonFields {
-    pub(crate) fn extend(&mut self, other: &CommonFields) {
+    /// Inherits the values not set from the given fields, i.e. of a base task or file.
+    ///
+    /// # Arguments
+    ///
+    /// * `other`: Fields to inherit from
+    /// * `inherited`: Returns the origin of an inherited env value or variable, given the
+    ///   origin it has in `other`
+    pub(crate) fn extend(&mut self, other: &CommonFields, inherited: impl Fn(Origin) -> Origin) {
         inherit_option_value!(self.wd, other.wd);
         // env_file should have been loaded into env
         // inherit_option_value!(self.env_file, other.env_file);
-        merge_map_values!(self.env, &other.env);
-        merge_map_values!(self.vars, &other.vars);
+        for (key, value) in &other.env {
+            if let Entry::Vacant(entry) = self.env.entry(key.clone()) {
+                entry.insert(value.clone());
+                self.env_origins
+                    .insert(key.clone(), inherited(other.get_env_origin(key)));
+            }
+        }
+        for (key, value) in &other.vars {
+            if let Entry::Vacant(entry) = self.vars.entry(key.clone()) {
+                entry.insert(value.clone());
+                self.vars_origins
+                    .insert(key.clone(), inherited(other.get_vars_origin(key)));
+            }
+        }
         merge_map_values!(self.incl, &other.incl);
     }
 
-    /// Loads the environment file into the environment variables
+    /// Merges the env, vars and templates of the task that called this one from its `cmds`.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent`: Fields of the parent task
+    /// * `parent_name`: Name of the parent task
+    /// * `parent_first`: Whether the values of the parent take precedence, which is the case
+    ///   for tasks called by name
+    pub(crate) fn merge_parent(
+        &mut self,
+        parent: &CommonFields,
+        parent_name: &str,
+        parent_first: bool,
+    ) {
+        let inherited = |origin| Origin::ParentTask(String::from(parent_name), Box::new(origin));
+        for (key, value) in &parent.env {
+            if parent_first || !self.env.contains_key(key) {
+                self.env.insert(key.clone(), value.clone());
+                self.env_origins
+                    .insert(key.clone(), inherited(parent.get_env_origin(key)));
+            }
+        }
+        for (key, value) in &parent.vars {
+            if parent_first || !self.vars.contains_key(key) {
+                self.vars.insert(key.clone(), value.clone());
+                self.vars_origins
+                    .insert(key.clone(), inherited(parent.get_vars_origin(key)));
+            }
+        }
+        for (key, value) in &parent.incl {
+            if parent_first || !self.incl.contains_key(key) {
+                self.incl.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    /// Returns where the env value with the given key comes from
+    pub(crate) fn get_env_origin(&self, key: &str) -> Origin {
+        self.env_origins
+            .get(key)
+            .cloned()
+            .unwrap_or(Origin::Declared)
+    }
+
+    /// Returns where the variable with the given key comes from
+    pub(crate) fn get_vars_origin(&self, key: &str) -> Origin {
+        self.vars_origins
+            .get(key)
+            .cloned()
+            .unwrap_or(Origin::Declared)
+    }
+
+    /// Returns the errors reading the environment and vars files, without loading them.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_path`: path to use as a reference to resolve relative paths
+    pub(crate) fn check_files(&self, base_path: &Path) -> Vec<ReadFileError> {
+        let mut problems = Vec::new();
+        for env_file in self.dotenv.iter() {
+            let env_file = get_path_relative_to_base(base_path, &env_file);
+            if let Err(e) = read_env_file(env_file.as_path()) {
+                problems.push(ReadFileError {
+                    path: env_file,
+                    message: e.to_string(),
+                });
+            }
+        }
+        for vars_file in self.vars_file.iter() {
+            let vars_file = get_path_relative_to_base(base_path, &vars_file);
+            if let Err(e) = read_vars_file(vars_file.as_path()) {
+                problems.push(ReadFileError {
+                    path: vars_file,
+                    message: e.to_string(),
+                });
+            }
+        }
+        problems
+    }
+
+    /// Loads the environment files into the environment variables, and the vars files into the
+    /// variables
     ///
     /// # Arguments
     ///
@@ -124,9 +296,33 @@ impl CommonFields {
         let envfiles = mem::take(&mut self.dotenv);
         for env_file in envfiles.iter() {
             let env_file = get_path_relative_to_base(base_path, &env_file);
-            let env_variables = read_env_file(env_file.as_path())?;
+            let env_variables = read_env_file(env_file.as_path()).map_err(|e| ReadFileError {
+                path: env_file.clone(),
+                message: e.to_string(),
+            })?;
             for (key, val) in env_variables {
-                self.env.entry(key).or_insert(val);
+                if let Entry::Vacant(entry) = self.env.entry(key.clone()) {
+                    entry.insert(EnvValue::Value(val));
+                    self.env_origins
+                        .insert(key, Origin::Dotenv(env_file.clone()));
+                }
+            }
+        }
+
+        // removes the vars_file as we won't need it again
+        let vars_files = mem::take(&mut self.vars_file);
+        for vars_file in vars_files.iter() {
+            let vars_file = get_path_relative_to_base(base_path, &vars_file);
+            let vars = read_vars_file(vars_file.as_path()).map_err(|e| ReadFileError {
+                path: vars_file.clone(),
+                message: e.to_string(),
+            })?;
+            for (key, val) in vars {
+                if let Entry::Vacant(entry) = self.vars.entry(key.clone()) {
+                    entry.insert(val);
+                    self.vars_origins
+                        .insert(key, Origin::VarsFile(vars_file.clone()));
+                }
             }
         }
 
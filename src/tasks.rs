@@ -3,35 +3,60 @@
 mod tasks_test;
 
 use std::borrow::Cow;
-use std::collections::{BTreeMap, HashMap};
-use std::env::temp_dir;
+use std::collections::{HashMap, HashSet};
+use std::env::{self, temp_dir};
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
-use std::{fmt, fs, mem};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::Duration;
+use std::{fmt, fs, mem, thread};
 
 use crate::args::ArgsContext;
 use crate::builtin_commands::get_builtin_command;
+use crate::command_options::{CommandOptions, DurationValue};
 use crate::defaults::default_false;
+use crate::dynamic_values::{DynamicValues, EnvValue};
 use crate::errors::{AwareTaskError, TaskError};
+use crate::fingerprints::{
+    all_patterns_match, get_definition_fingerprint, get_fingerprint, read_fingerprint,
+    write_fingerprint, FingerprintMethod,
+};
 use crate::inherit_option_value;
 use crate::mom_files::MomFile;
+use crate::params::{get_param_values, Param};
 use crate::print_utils::{MomOutput, INFO_COLOR};
-use crate::serde_common::CommonFields;
+use crate::run_context::{set_interrupt_handler, RunContext};
+use crate::serde_common::{CommonFields, Origin, StringOrVecString};
 use crate::tera::get_tera_instance;
 use colored::Colorize;
+use petgraph::algo::toposort;
+use petgraph::visit::Dfs;
 use serde::{de, Deserialize, Serialize};
 
 use crate::types::DynErrResult;
 use crate::utils::{
-    expand_arg, expand_args, get_working_directory, join_commands, split_command,
-    TMP_FOLDER_NAMESPACE,
+    expand_arg, expand_args, find_cycle, get_env_diff, get_task_deps_graph, get_working_directory,
+    join_commands, split_command, TMP_FOLDER_NAMESPACE,
 };
 use md5::{Digest, Md5};
 
 pub const DRY_RUN_MESSAGE: &str = "Dry run mode, nothing executed.";
 
+/// How often to check if a running child finished or needs to be killed.
+const CHILD_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Time a child that reached its timeout has to terminate before it is killed.
+#[cfg(unix)]
+const TERMINATE_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Environment variables and variables of a task given directly, along with the dynamic ones.
+type EnvAndVars = (
+    HashMap<String, String>,
+    HashMap<String, serde_yaml::Value>,
+    DynamicValues,
+);
+
 cfg_if::cfg_if! {
     if #[cfg(target_os = "windows")] {
         // Will run the actual script in CMD, but we don't need to specify /C option
@@ -60,12 +85,119 @@ cfg_if::cfg_if! {
             Ok(OpenOptions::new()
             .create(true)
             .write(true)
+            .truncate(true)
             .mode(0o770)  // Create with appropriate permission
             .open(path)?)
         }
     }
 }
 
+cfg_if::cfg_if! {
+    if #[cfg(unix)] {
+        use std::os::unix::process::{CommandExt, ExitStatusExt};
+        use std::time::Instant;
+
+        /// Returns the signal that terminated the process, if any.
+        fn get_exit_signal(status: &ExitStatus) -> Option<i32> {
+            status.signal()
+        }
+
+        /// Makes the command spawn in its own process group.
+        fn isolate_command(command: &mut Command) {
+            command.process_group(0);
+        }
+
+        /// Sends the given signal to the process group of an isolated child.
+        fn signal_process_group(child: &Child, signal: libc::c_int) {
+            // The child is the leader of its process group, so the group id is the child id
+            unsafe {
+                libc::kill(-(child.id() as libc::pid_t), signal);
+            }
+        }
+
+        /// Kills the child, including its own children if it is isolated.
+        fn kill_child(child: &mut Child, isolated: bool) {
+            if isolated {
+                signal_process_group(child, libc::SIGKILL);
+            } else {
+                child.kill().unwrap_or(());
+            }
+        }
+
+        /// Forwards Ctrl-C to an isolated child, as it does not receive it from the terminal.
+        fn interrupt_child(child: &Child) {
+            signal_process_group(child, libc::SIGINT);
+        }
+
+        /// Asks the child to terminate with SIGTERM, and kills it if it is still running after
+        /// the grace period. The whole process group is signaled if the child is isolated,
+        /// otherwise only the child, as it shares the process group of mom.
+        fn terminate_child(child: &mut Child, isolated: bool) -> std::io::Result<()> {
+            if isolated {
+                signal_process_group(child, libc::SIGTERM);
+            } else {
+                unsafe {
+                    libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+                }
+            }
+            let start = Instant::now();
+            while start.elapsed() < TERMINATE_GRACE_PERIOD {
+                if child.try_wait()?.is_some() {
+                    return Ok(());
+                }
+                thread::sleep(CHILD_POLL_INTERVAL);
+            }
+            kill_child(child, isolated);
+            child.wait()?;
+            Ok(())
+        }
+    } else {
+        use std::os::windows::process::CommandExt;
+
+        /// Processes are not terminated by signals in Windows.
+        fn get_exit_signal(_status: &ExitStatus) -> Option<i32> {
+            None
+        }
+
+        /// Creates the process in a new process group.
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+
+        /// Makes the command spawn in its own process group.
+        fn isolate_command(command: &mut Command) {
+            command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+        }
+
+        /// Kills the process tree of the child.
+        fn kill_process_tree(child: &Child) {
+            let _ = Command::new("taskkill")
+                .args(["/T", "/F", "/PID", &child.id().to_string()])
+                .status();
+        }
+
+        /// Kills the child, including its own children if it is isolated.
+        fn kill_child(child: &mut Child, isolated: bool) {
+            if isolated {
+                kill_process_tree(child);
+            } else {
+                child.kill().unwrap_or(());
+            }
+        }
+
+        /// Ctrl-C cannot be forwarded to a process group in Windows, so the child is killed.
+        fn interrupt_child(child: &Child) {
+            kill_process_tree(child);
+        }
+
+        /// There is no SIGTERM in Windows, so the child is killed, along with its process tree if
+        /// it is isolated.
+        fn terminate_child(child: &mut Child, isolated: bool) -> std::io::Result<()> {
+            kill_child(child, isolated);
+            child.wait()?;
+            Ok(())
+        }
+    }
+}
+
 /// Creates a temporal script returns the path to it.
 /// The OS should take care of cleaning the file.
 ///
@@ -110,20 +242,9 @@ fn get_temp_script(
     Ok(path)
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
-pub(crate) struct TaskNameOption {
-    task: String,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub(crate) struct CmdOption {
-    #[serde(flatten)]
-    command: String,
-}
-
 #[derive(Debug, Serialize, Clone)]
 #[serde(untagged)]
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum Cmd {
     #[serde(rename = "task_name")]
     TaskName(String),
@@ -131,6 +252,25 @@ pub(crate) enum Cmd {
     Task(Box<Task>),
     #[serde(rename = "cmd")]
     Cmd(String),
+    #[serde(rename = "parallel")]
+    Parallel(Vec<Cmd>),
+    #[serde(rename = "inline")]
+    Inline(InlineCmd),
+}
+
+/// Command given as an inline `cmd` map, with options to run it
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct InlineCmd {
+    /// Command to run
+    pub(crate) cmd: String,
+
+    /// Name of the variable the output of the command is stored in, for the next commands
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) register: Option<String>,
+
+    /// Options that take precedence over the ones of the task
+    #[serde(flatten)]
+    pub(crate) options: CommandOptions,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -140,6 +280,26 @@ pub(crate) enum StringOrTask {
     Task(Box<Task>),
 }
 
+/// Keys accepted in a map of the cmds option
+const CMD_FIELDS: &[&str] = &[
+    "task",
+    "cmd",
+    "parallel",
+    "ignore_error",
+    "ok_exit_codes",
+    "retries",
+    "retry_delay",
+    "retry_backoff",
+    "timeout",
+    "register",
+];
+
+/// Returns the error for a `task` or `parallel` key given along with other keys in a map of
+/// `cmds`, as they cannot take options.
+fn combined_key_error<E: de::Error>(key: &str, other_key: &str) -> E {
+    E::custom(format!("`{}` cannot be combined with `{}`", key, other_key))
+}
+
 impl<'de> de::Deserialize<'de> for Cmd {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -151,7 +311,7 @@ impl<'de> de::Deserialize<'de> for Cmd {
             type Value = Cmd;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("cmd, task name or task")
+                formatter.write_str("cmd, task name, task or parallel group")
             }
 
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -167,23 +327,70 @@ impl<'de> de::Deserialize<'de> for Cmd {
             {
                 match map.next_key::<String>()? {
                     Some(key) => match key.as_str() {
-                        "task" => {
-                            let string_or_task: StringOrTask = map.next_value()?;
-                            match string_or_task {
-                                StringOrTask::String(s) => Ok(Cmd::TaskName(s)),
-                                StringOrTask::Task(t) => Ok(Cmd::Task(t)),
+                        "task" | "parallel" => {
+                            let cmd = if key == "task" {
+                                match map.next_value::<StringOrTask>()? {
+                                    StringOrTask::String(s) => Cmd::TaskName(s),
+                                    StringOrTask::Task(t) => Cmd::Task(t),
+                                }
+                            } else {
+                                Cmd::Parallel(map.next_value()?)
+                            };
+                            match map.next_key::<String>()? {
+                                Some(other_key) => Err(combined_key_error(&key, &other_key)),
+                                None => Ok(cmd),
                             }
                         }
-                        "cmd" => {
-                            let cmd: String = map.next_value()?;
-                            Ok(Cmd::Cmd(cmd))
+                        // The options can be given before the command, so all keys are read
+                        _ => {
+                            let mut cmd: Option<String> = None;
+                            let mut register: Option<String> = None;
+                            let mut options = CommandOptions::default();
+                            let first_key = key.clone();
+                            let mut key = Some(key);
+                            while let Some(current) = key {
+                                match current.as_str() {
+                                    "cmd" => cmd = Some(map.next_value()?),
+                                    "ignore_error" => {
+                                        options.ignore_error = Some(map.next_value()?)
+                                    }
+                                    "ok_exit_codes" => {
+                                        options.ok_exit_codes = Some(map.next_value()?)
+                                    }
+                                    "retries" => options.retries = Some(map.next_value()?),
+                                    "retry_delay" => options.retry_delay = Some(map.next_value()?),
+                                    "retry_backoff" => {
+                                        options.retry_backoff = Some(map.next_value()?)
+                                    }
+                                    "timeout" => options.timeout = Some(map.next_value()?),
+                                    "register" => register = Some(map.next_value()?),
+                                    "task" | "parallel" => {
+                                        return Err(combined_key_error(&current, &first_key))
+                                    }
+                                    _ => {
+                                        return Err(de::Error::unknown_field(
+                                            current.as_str(),
+                                            CMD_FIELDS,
+                                        ))
+                                    }
+                                }
+                                key = map.next_key::<String>()?;
+                            }
+                            let cmd = cmd.ok_or_else(|| de::Error::missing_field("cmd"))?;
+                            options.validate().map_err(de::Error::custom)?;
+                            if options.is_empty() && register.is_none() {
+                                Ok(Cmd::Cmd(cmd))
+                            } else {
+                                Ok(Cmd::Inline(InlineCmd {
+                                    cmd,
+                                    register,
+                                    options,
+                                }))
+                            }
                         }
-                        _ => Err(de::Error::unknown_field(key.as_str(), &["task", "cmd"])),
                     },
                     None => Err(de::Error::missing_field("task_name or task")),
                 }
-
-                // Deserialize::deserialize(de::value::MapAccessDeserializer::new(map))
             }
         }
 
@@ -198,30 +405,110 @@ impl TaskCondition {
     pub(crate) fn holds(
         &self,
         task_name: &str,
-        tera: &mut tera::Tera,
-        context: &tera::Context,
-        env: &HashMap<String, String>,
+        resolved: &mut ResolvedTask,
     ) -> Result<bool, AwareTaskError> {
         let template_name = format!("{}.condition", task_name);
-        tera.add_raw_template(&template_name, &self.0)
-            .map_err(|e| {
-                AwareTaskError::new(
-                    task_name,
-                    TaskError::ConfigError(format!("Invalid condition: {}", e)),
-                )
-            })?;
-        let result = tera.render(&template_name, context).map_err(|e| {
+        let condition_error = |e: tera::Error| {
             AwareTaskError::new(
                 task_name,
-                TaskError::ConfigError(format!("Invalid condition: {}", e)),
+                TaskError::Template {
+                    field: String::from("condition"),
+                    reason: format!("Invalid condition: {}", e),
+                },
             )
-        })?;
-        let result = expand_arg(&result, env);
+        };
+        resolved
+            .tera_instance
+            .add_raw_template(&template_name, &self.0)
+            .map_err(condition_error)?;
+        let result = resolved
+            .tera_instance
+            .render(&template_name, &resolved.tera_context)
+            .map_err(condition_error)?;
+        let result = expand_arg(&result, &resolved.env);
         let result = result.trim().to_lowercase();
         Ok(result == "true")
     }
 }
 
+/// What a task runs, given by the first of `script`, `program` or `cmds` it sets.
+pub(crate) enum TaskBody<'a> {
+    /// Script and the runner it runs with, which defaults to the one of the OS
+    Script {
+        script: &'a str,
+        script_runner: &'a str,
+    },
+    /// Program and its arguments
+    Program { program: &'a str, args: &'a str },
+    /// Commands, which run concurrently if `parallel` is set
+    Cmds { cmds: &'a [Cmd], parallel: bool },
+    /// The task does not set anything to run
+    Nothing,
+}
+
+/// Task resolved to render its templates: its env, vars and params, merged from every place
+/// they can be given, and the Tera instance and context the templates are rendered with.
+/// Running and explaining a task resolve it the same way, so that both see the same values.
+#[derive(Clone)]
+pub(crate) struct ResolvedTask {
+    pub(crate) env: HashMap<String, String>,
+    pub(crate) vars: HashMap<String, serde_yaml::Value>,
+    pub(crate) params: HashMap<String, tera::Value>,
+    tera_instance: tera::Tera,
+    tera_context: tera::Context,
+}
+
+impl ResolvedTask {
+    /// Renders the given template of the task.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_name`: Name of the task the template belongs to
+    /// * `field`: Path of the option the template is given in, i.e. `cmds.0`
+    /// * `template`: Template to render
+    pub(crate) fn render(
+        &mut self,
+        task_name: &str,
+        field: &str,
+        template: &str,
+    ) -> Result<String, TaskError> {
+        let template_name = format!("tasks.{}.{}", task_name, field);
+        let template_error = |e| TaskError::template(field, e);
+        self.tera_instance
+            .add_raw_template(&template_name, template)
+            .map_err(template_error)?;
+        self.tera_instance
+            .render(&template_name, &self.tera_context)
+            .map_err(template_error)
+    }
+
+    /// Makes the given value available to the templates rendered next.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: Name of the value in the templates, i.e. `script_path`
+    /// * `value`: Value to insert
+    pub(crate) fn insert<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) {
+        self.tera_context.insert(key, value);
+    }
+
+    /// Stores the output of a command, trimmed, in the `vars` of the context, so that the next
+    /// commands can use it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the variable to store the output in
+    /// * `output` - Output of the command
+    pub(crate) fn register_output(&mut self, name: &str, output: &str) {
+        let mut vars = match self.tera_context.remove("vars") {
+            Some(tera::Value::Object(vars)) => vars,
+            _ => tera::Map::new(),
+        };
+        vars.insert(String::from(name), tera::Value::from(output.trim()));
+        self.tera_context.insert("vars", &vars);
+    }
+}
+
 /// Represents a Task
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
@@ -230,6 +517,11 @@ pub(crate) struct Task {
     #[serde(skip_deserializing)]
     pub(crate) name: String,
 
+    /// Name the task is defined with in its mom file, if it differs from `name`, i.e. when it
+    /// runs from the commands of another task
+    #[serde(skip)]
+    defined_name: Option<String>,
+
     #[serde(flatten)]
     pub(crate) common: CommonFields,
 
@@ -239,6 +531,9 @@ pub(crate) struct Task {
     /// Help of the task
     help: Option<String>,
 
+    /// Parameters the task accepts
+    params: Option<Vec<Param>>,
+
     /// Script to run
     script: Option<String>,
 
@@ -258,6 +553,56 @@ pub(crate) struct Task {
     /// Run commands
     cmds: Option<Vec<Cmd>>,
 
+    /// Run the commands concurrently
+    parallel: Option<bool>,
+
+    /// Maximum number of commands to run concurrently
+    jobs: Option<usize>,
+
+    /// Whether to cancel the remaining commands when one fails
+    fail_fast: Option<bool>,
+
+    /// Whether to continue if a command fails, reporting the error as a warning
+    ignore_error: Option<bool>,
+
+    /// Exit codes of the commands considered successful. Defaults to 0 only
+    ok_exit_codes: Option<Vec<i32>>,
+
+    /// How many times to run a command again if it fails
+    retries: Option<u32>,
+
+    /// Time to wait before the first retry of a command
+    retry_delay: Option<DurationValue>,
+
+    /// Factor the retry delay is multiplied by after each retry
+    retry_backoff: Option<f64>,
+
+    /// Time the task can run for, after which it is terminated. Unlike the other command
+    /// options, it applies to the task as a whole, not to each command
+    timeout: Option<DurationValue>,
+
+    /// Commands to run after the task, even if it fails or is interrupted
+    finally: Option<Vec<Cmd>>,
+
+    /// Tasks to run before this task, only once per invocation
+    #[serde(default)]
+    deps: StringOrVecString,
+
+    /// Files the task depends on. The task is skipped if none changed since the last run
+    #[serde(default)]
+    sources: StringOrVecString,
+
+    /// Files the task generates. The task is not skipped if any is missing
+    #[serde(default)]
+    generates: StringOrVecString,
+
+    /// Method used to detect changes in the sources
+    fingerprint: Option<FingerprintMethod>,
+
+    /// Files to watch for changes when running in watch mode. Defaults to the sources
+    #[serde(default)]
+    watch: StringOrVecString,
+
     /// Extends args from bases
     #[serde(alias = "args+")]
     args_extend: Option<String>,
@@ -280,24 +625,74 @@ impl Task {
     /// Returns the dependencies of the task.
     pub(crate) fn get_dependencies(&self) -> Vec<&str> {
         let mut dependencies: Vec<&str> = self.common.extend.iter().collect();
+        dependencies.extend(self.get_deps());
 
         if let Some(cmds) = &self.cmds {
+            Self::collect_cmds_dependencies(cmds, &mut dependencies);
+        }
+
+        if let Some(finally) = &self.finally {
+            Self::collect_cmds_dependencies(finally, &mut dependencies);
+        }
+
+        dependencies
+    }
+
+    /// Returns the tasks of this file that must run before this task. The ones of included
+    /// files are left out, as they are resolved when they run, and cannot depend on the tasks
+    /// of this file.
+    pub(crate) fn get_deps(&self) -> Vec<&str> {
+        self.deps
+            .iter()
+            .filter(|name| !name.contains(':'))
+            .collect()
+    }
+
+    /// Returns the tasks of included files this task refers to, with their namespace, in `deps`
+    /// and `cmds`.
+    pub(crate) fn get_included_dependencies(&self) -> Vec<&str> {
+        fn collect<'a>(cmds: &'a [Cmd], dependencies: &mut Vec<&'a str>) {
             for cmd in cmds {
                 match cmd {
-                    Cmd::TaskName(task_name) => {
-                        dependencies.push(task_name);
+                    Cmd::TaskName(task_name) if task_name.contains(':') => {
+                        dependencies.push(task_name)
                     }
-                    Cmd::Task(task) => {
-                        dependencies.append(&mut task.get_dependencies());
-                    }
-                    Cmd::Cmd(_) => {}
+                    Cmd::Task(task) => dependencies.append(&mut task.get_included_dependencies()),
+                    Cmd::Parallel(cmds) => collect(cmds, dependencies),
+                    Cmd::TaskName(_) | Cmd::Cmd(_) | Cmd::Inline(_) => {}
                 }
             }
         }
 
+        let mut dependencies: Vec<&str> =
+            self.deps.iter().filter(|name| name.contains(':')).collect();
+        for cmds in [&self.cmds, &self.finally].into_iter().flatten() {
+            collect(cmds, &mut dependencies);
+        }
         dependencies
     }
 
+    /// Appends the tasks the given commands depend on to `dependencies`.
+    fn collect_cmds_dependencies<'a>(cmds: &'a [Cmd], dependencies: &mut Vec<&'a str>) {
+        for cmd in cmds {
+            match cmd {
+                // Tasks of included files are resolved when they run. They cannot depend on the
+                // tasks of this file, as files cannot include each other.
+                Cmd::TaskName(task_name) if task_name.contains(':') => {}
+                Cmd::TaskName(task_name) => {
+                    dependencies.push(task_name);
+                }
+                Cmd::Task(task) => {
+                    dependencies.append(&mut task.get_dependencies());
+                }
+                Cmd::Parallel(cmds) => {
+                    Self::collect_cmds_dependencies(cmds, dependencies);
+                }
+                Cmd::Cmd(_) | Cmd::Inline(_) => {}
+            }
+        }
+    }
+
     /// Does extra setup on the task and does some validation.
     ///
     /// # Arguments
@@ -335,56 +730,200 @@ impl Task {
         &self,
         args: &ArgsContext,
         mom_file: &MomFile,
-        dry_run: bool,
+        ctx: &RunContext,
     ) -> Result<(), AwareTaskError> {
-        let env = self.get_env(&mom_file.common.env);
-        let vars = self.get_vars(&mom_file.common.vars);
-
-        let mut tera_instance = self
-            .get_tera_instance(mom_file, env.clone())
+        let (mut env, mut vars, dynamic) = self.get_env_and_vars(mom_file, ctx);
+        let params = get_param_values(self.get_params(), args, &mom_file.directory)
             .map_err(|e| AwareTaskError::new(&self.name, e))?;
-        let mut tera_context = self.get_tera_context(args, mom_file, &env, &vars);
 
         if let Some(condition) = &self.condition {
-            if !condition.holds(&self.name, &mut tera_instance, &tera_context, &env)? {
+            let mut templates = self.get_incl_templates(mom_file);
+            templates.push(&condition.0);
+            if dynamic.are_used_by(&templates) {
+                dynamic
+                    .resolve(&templates, &mut env, &mut vars, ctx)
+                    .map_err(|e| AwareTaskError::new(&self.name, e))?;
+            }
+            let mut resolved = self
+                .resolve(args, mom_file, env.clone(), vars.clone(), params.clone())
+                .map_err(|e| AwareTaskError::new(&self.name, e))?;
+            if !condition.holds(&self.name, &mut resolved)? {
                 println!("{}", format!("{} skipped", &self.name).mom_info());
                 return Ok(());
             }
         }
 
-        let result = if self.script.is_some() {
-            self.run_script(
-                mom_file,
-                &env,
-                &mut tera_instance,
-                &mut tera_context,
-                dry_run,
-            )
-        } else if self.program.is_some() {
-            self.run_program(
-                mom_file,
-                &env,
-                &mut tera_instance,
-                &mut tera_context,
-                dry_run,
-            )
-        } else if self.cmds.is_some() {
-            self.run_cmds(
-                args,
-                mom_file,
-                &env,
-                &mut tera_instance,
-                &mut tera_context,
-                dry_run,
-            )
-        } else {
-            Err(TaskError::ConfigError(String::from("Nothing to run.")))
+        self.run_deps(mom_file, ctx)
+            .map_err(|e| AwareTaskError::new(&self.name, e))?;
+
+        match self.is_up_to_date(args, mom_file, ctx) {
+            Ok(true) => {
+                println!("{}", format!("{} up to date", &self.name).mom_info());
+                return Ok(());
+            }
+            Ok(false) => {}
+            Err(e) => {
+                return Err(AwareTaskError::new(
+                    &self.name,
+                    TaskError::RuntimeError(format!("Error checking the sources: {}", e)),
+                ))
+            }
+        }
+
+        // Only computed once the task is going to run its commands
+        dynamic
+            .resolve(&self.get_templates(mom_file), &mut env, &mut vars, ctx)
+            .map_err(|e| AwareTaskError::new(&self.name, e))?;
+        let mut resolved = self
+            .resolve(args, mom_file, env, vars, params)
+            .map_err(|e| AwareTaskError::new(&self.name, e))?;
+
+        if self.finally.is_some() {
+            // Otherwise Ctrl-C would terminate mom before running the finally commands
+            set_interrupt_handler();
+        }
+
+        let body_ctx = match self.timeout {
+            Some(timeout) => ctx.with_timeout(&self.name, timeout),
+            None => ctx.clone(),
+        };
+        let result = match self.get_body() {
+            TaskBody::Script {
+                script,
+                script_runner,
+            } => self.run_script(script, script_runner, mom_file, &mut resolved, &body_ctx),
+            TaskBody::Program { program, args } => {
+                self.run_program(program, args, mom_file, &mut resolved, &body_ctx)
+            }
+            TaskBody::Cmds {
+                cmds,
+                parallel: true,
+            } => self.run_cmds_parallel(cmds, "cmds", args, mom_file, &resolved, &body_ctx),
+            TaskBody::Cmds {
+                cmds,
+                parallel: false,
+            } => self.run_cmds(cmds, "cmds", args, mom_file, &mut resolved, &body_ctx),
+            TaskBody::Nothing => Err(TaskError::ConfigError(String::from("Nothing to run."))),
         };
 
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => Err(AwareTaskError::new(&self.name, e)),
+        let result = match &self.finally {
+            Some(finally) => {
+                // Shielded, so that it runs even if the task was cancelled or interrupted
+                let finally_result = self.run_cmds(
+                    finally,
+                    "finally",
+                    args,
+                    mom_file,
+                    &mut resolved,
+                    &ctx.shielded(),
+                );
+                match (result, finally_result) {
+                    (Err(e), Err(finally_e)) => {
+                        // The original error is the one returned, as it is the cause
+                        let message =
+                            format!("Task `{}` finally failed:\n{}", self.name, finally_e);
+                        eprintln!("{}", message.mom_error());
+                        Err(e)
+                    }
+                    (Err(e), Ok(_)) => Err(e),
+                    (Ok(_), finally_result) => finally_result,
+                }
+            }
+            None => result,
+        };
+
+        if let Err(e) = result {
+            return Err(AwareTaskError::new(&self.name, e));
+        }
+
+        if !ctx.dry_run {
+            self.store_fingerprint(args, mom_file, ctx).map_err(|e| {
+                AwareTaskError::new(
+                    &self.name,
+                    TaskError::RuntimeError(format!("Error storing the fingerprint: {}", e)),
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the task can be skipped because none of its `sources` changed since the
+    /// last successful run, nor its definition or arguments, and all of its `generates` exist.
+    fn is_up_to_date(
+        &self,
+        args: &ArgsContext,
+        mom_file: &MomFile,
+        ctx: &RunContext,
+    ) -> DynErrResult<bool> {
+        if self.sources.is_empty() {
+            return Ok(false);
         }
+        let stored = match read_fingerprint(&mom_file.filepath, self.get_defined_name()) {
+            Some(stored) => stored,
+            None => return Ok(false),
+        };
+        let current = self.get_sources_fingerprint(args, mom_file, ctx)?;
+        Ok(stored == current && all_patterns_match(&mom_file.directory, &self.generates)?)
+    }
+
+    /// Stores the fingerprint of the `sources` after a successful run, if any.
+    fn store_fingerprint(
+        &self,
+        args: &ArgsContext,
+        mom_file: &MomFile,
+        ctx: &RunContext,
+    ) -> DynErrResult<()> {
+        if self.sources.is_empty() {
+            return Ok(());
+        }
+        let fingerprint = self.get_sources_fingerprint(args, mom_file, ctx)?;
+        write_fingerprint(&mom_file.filepath, self.get_defined_name(), &fingerprint)
+    }
+
+    /// Returns the fingerprint of the `sources`, combined with the one of the definition of the
+    /// task, its env and vars and the arguments it runs with, so that the task is not skipped
+    /// if what it runs changed.
+    fn get_sources_fingerprint(
+        &self,
+        args: &ArgsContext,
+        mom_file: &MomFile,
+        ctx: &RunContext,
+    ) -> DynErrResult<String> {
+        /// Everything that decides what the task runs
+        #[derive(Serialize)]
+        struct Definition<'a> {
+            task: Task,
+            env: HashMap<String, EnvValue>,
+            vars: HashMap<String, serde_yaml::Value>,
+            args: &'a ArgsContext,
+        }
+
+        let mut env = self.get_env(&mom_file.common.env);
+        let mut vars = self.get_vars(&mom_file.common.vars);
+        ctx.overrides.apply(&mut env, &mut vars);
+        // The name is left out, as it depends on the task it runs from
+        let task = Task {
+            name: String::new(),
+            ..self.clone()
+        };
+        let definition = get_definition_fingerprint(&Definition {
+            task,
+            env,
+            vars,
+            args,
+        })?;
+        let sources = get_fingerprint(
+            &mom_file.directory,
+            &self.sources,
+            self.fingerprint.unwrap_or_default(),
+        )?;
+        Ok(format!("{}{}", definition, sources))
+    }
+
+    /// Returns the name the task is defined with in its mom file, which its fingerprint is
+    /// stored by.
+    fn get_defined_name(&self) -> &str {
+        self.defined_name.as_deref().unwrap_or(&self.name)
     }
 
     /// Extends from the given task.
@@ -397,20 +936,43 @@ impl Task {
     ///
     pub(crate) fn extend(&mut self, base_task: &Task) {
         inherit_option_value!(self.help, base_task.help);
+        inherit_option_value!(self.params, base_task.params);
         inherit_option_value!(self.script, base_task.script);
         inherit_option_value!(self.script_runner, base_task.script_runner);
         inherit_option_value!(self.script_extension, base_task.script_extension);
         inherit_option_value!(self.program, base_task.program);
         inherit_option_value!(self.args, base_task.args);
         inherit_option_value!(self.cmds, base_task.cmds);
+        inherit_option_value!(self.finally, base_task.finally);
+        inherit_option_value!(self.parallel, base_task.parallel);
+        inherit_option_value!(self.jobs, base_task.jobs);
+        inherit_option_value!(self.fail_fast, base_task.fail_fast);
+        inherit_option_value!(self.ignore_error, base_task.ignore_error);
+        inherit_option_value!(self.ok_exit_codes, base_task.ok_exit_codes);
+        inherit_option_value!(self.retries, base_task.retries);
+        inherit_option_value!(self.retry_delay, base_task.retry_delay);
+        inherit_option_value!(self.retry_backoff, base_task.retry_backoff);
+        inherit_option_value!(self.timeout, base_task.timeout);
+        if self.deps.is_empty() {
+            self.deps = base_task.deps.clone();
+        }
+        if self.sources.is_empty() {
+            self.sources = base_task.sources.clone();
+        }
+        if self.generates.is_empty() {
+            self.generates = base_task.generates.clone();
+        }
+        inherit_option_value!(self.fingerprint, base_task.fingerprint);
+        if self.watch.is_empty() {
+            self.watch = base_task.watch.clone();
+        }
         inherit_option_value!(self.condition, base_task.condition);
-        self.common.extend(&base_task.common);
+        self.common.extend(&base_task.common, |origin| {
+            Origin::BaseTask(base_task.name.clone(), Box::new(origin))
+        });
 
         if self.args_extend.is_some() {
             let new_args = mem::take(&mut self.args_extend).unwrap();
-            if self.args.is_none() {
-                self.args = mem::replace(&mut self.args, Some(String::new()));
-            }
             if let Some(args) = &mut self.args {
                 args.push(' ');
                 args.push_str(&new_args);
@@ -425,11 +987,47 @@ impl Task {
         &self.name
     }
 
+    /// Returns the glob patterns of the files to watch in watch mode, which default to the
+    /// sources of the task.
+    pub(crate) fn get_watch_patterns(&self) -> &StringOrVecString {
+        if self.watch.is_empty() {
+            &self.sources
+        } else {
+            &self.watch
+        }
+    }
+
     /// Returns weather the task is private or not
     pub(crate) fn is_private(&self) -> bool {
         self.private
     }
 
+    /// Returns whether the task has a condition to check before running
+    pub(crate) fn has_condition(&self) -> bool {
+        self.condition.is_some()
+    }
+
+    /// Returns the parameters the task accepts
+    pub(crate) fn get_params(&self) -> &[Param] {
+        match self.params {
+            Some(ref params) => params,
+            None => &[],
+        }
+    }
+
+    /// Returns the options that apply to every command of the task. The timeout is not
+    /// included, as it applies to the task as a whole.
+    fn get_command_options(&self) -> CommandOptions {
+        CommandOptions {
+            ignore_error: self.ignore_error,
+            ok_exit_codes: self.ok_exit_codes.clone(),
+            retries: self.retries,
+            retry_delay: self.retry_delay,
+            retry_backoff: self.retry_backoff,
+            timeout: None,
+        }
+    }
+
     /// Returns the help for the task
     pub(crate) fn get_help(&self) -> &str {
         match self.help {
@@ -446,7 +1044,7 @@ impl Task {
     /// * `mom_file`: mom file to load extra environment variables from
     ///
     /// returns: HashMap<String, String, RandomState>
-    fn get_env(&self, env: &HashMap<String, String>) -> HashMap<String, String> {
+    fn get_env(&self, env: &HashMap<String, EnvValue>) -> HashMap<String, EnvValue> {
         let mut new_env = self.common.env.clone();
         for (key, val) in env {
             new_env.entry(key.clone()).or_insert_with(|| val.clone());
@@ -473,17 +1071,6 @@ impl Task {
         new_vars
     }
 
-    /// Same as `get_env` but for the tera templates
-    fn get_templates(&self, tera_templates: &BTreeMap<String, String>) -> BTreeMap<String, String> {
-        let mut new_templates: BTreeMap<String, String> = self.common.incl.clone();
-        for (key, val) in tera_templates {
-            new_templates
-                .entry(key.clone())
-                .or_insert_with(|| val.clone());
-        }
-        new_templates
-    }
-
     /// Validates the task configuration.
     ///
     /// # Arguments
@@ -508,9 +1095,215 @@ impl Task {
             )));
         }
 
+        let mut param_names = HashSet::new();
+        for param in self.get_params() {
+            param.validate()?;
+            if !param_names.insert(&param.name) {
+                return Err(TaskError::ConfigError(format!(
+                    "Duplicate parameter `{}`.",
+                    param.name
+                )));
+            }
+        }
+
+        if self.jobs == Some(0) {
+            return Err(TaskError::ConfigError(String::from(
+                "`jobs` must be greater than 0.",
+            )));
+        }
+
+        self.get_command_options()
+            .validate()
+            .map_err(TaskError::ConfigError)?;
+
         Ok(())
     }
 
+    /// Checks the task without running it, and returns all the problems found. Besides
+    /// validating its configuration, the templates of the task are compiled, but not rendered,
+    /// as rendering needs the arguments and may run commands, i.e. through dynamic values.
+    ///
+    /// # Arguments
+    ///
+    /// * `mom_file`: mom file the task belongs to, with the `incl` templates it can use
+    pub(crate) fn check(&self, mom_file: &MomFile) -> Vec<TaskError> {
+        let mut problems = Vec::new();
+        if let Err(e) = self.validate() {
+            problems.push(e);
+        }
+
+        // Added first, as the other templates can include them. The ones of the mom file are
+        // reported when checking the file. Tera keeps templates that fail to extend others,
+        // which would break the next ones, so they are added to a copy first.
+        let mut tera = get_tera_instance(HashMap::new());
+        let incl = mom_file.common.incl.iter().map(|incl| (incl, false));
+        let incl = incl.chain(self.common.incl.iter().map(|incl| (incl, true)));
+        for ((name, template), report) in incl {
+            let mut new_tera = tera.clone();
+            match Self::add_template(&mut new_tera, &format!("incl.{name}"), template) {
+                Ok(_) => tera = new_tera,
+                Err(e) if report => problems.push(e),
+                Err(_) => {}
+            }
+        }
+        let templates = [
+            ("condition", self.condition.as_ref().map(|c| &c.0)),
+            ("script", self.script.as_ref()),
+            ("script_runner", self.script_runner.as_ref()),
+            ("args", self.args.as_ref()),
+            ("args+", self.args_extend.as_ref()),
+        ];
+        for (field, template) in templates {
+            if let Some(template) = template {
+                Self::check_template(&tera, field, template, &mut problems);
+            }
+        }
+        if let Some(cmds) = &self.cmds {
+            Self::check_cmds(&tera, "cmds", cmds, mom_file, &mut problems);
+        }
+        if let Some(finally) = &self.finally {
+            Self::check_cmds(&tera, "finally", finally, mom_file, &mut problems);
+        }
+        problems
+    }
+
+    /// Compiles the templates of the given commands, adding the problems found to `problems`.
+    fn check_cmds(
+        tera: &tera::Tera,
+        path: &str,
+        cmds: &[Cmd],
+        mom_file: &MomFile,
+        problems: &mut Vec<TaskError>,
+    ) {
+        for (i, cmd) in cmds.iter().enumerate() {
+            let cmd_path = format!("{path}.{i}");
+            match cmd {
+                Cmd::Cmd(cmd) => Self::check_template(tera, &cmd_path, cmd, problems),
+                Cmd::Inline(inline) => Self::check_template(tera, &cmd_path, &inline.cmd, problems),
+                Cmd::Parallel(cmds) => Self::check_cmds(tera, &cmd_path, cmds, mom_file, problems),
+                Cmd::Task(task) => {
+                    for problem in task.check(mom_file) {
+                        let reason = format!("In `{}.task`: {}", cmd_path, problem.reason());
+                        problems.push(match problem {
+                            TaskError::Template { field, .. } => TaskError::Template {
+                                field: format!("{cmd_path}.task.{field}"),
+                                reason,
+                            },
+                            _ => TaskError::ConfigError(reason),
+                        });
+                    }
+                }
+                // Checked when the task it refers to is checked
+                Cmd::TaskName(_) => {}
+            }
+        }
+    }
+
+    /// Compiles the template, adding a problem to `problems` if it is invalid. A copy of the
+    /// Tera instance is used, so that the template is not kept.
+    fn check_template(
+        tera: &tera::Tera,
+        field: &str,
+        template: &str,
+        problems: &mut Vec<TaskError>,
+    ) {
+        if let Err(e) = Self::add_template(&mut tera.clone(), field, template) {
+            problems.push(e);
+        }
+    }
+
+    /// Adds the template to the Tera instance, returning a problem if it is invalid.
+    fn add_template(tera: &mut tera::Tera, field: &str, template: &str) -> Result<(), TaskError> {
+        tera.add_raw_template(field, template).map_err(|e| {
+            let reason = TaskError::template(field, e).reason();
+            TaskError::Template {
+                field: String::from(field),
+                reason: format!("Invalid template in `{}`: {}", field, reason),
+            }
+        })
+    }
+
+    /// Returns what the task runs.
+    pub(crate) fn get_body(&self) -> TaskBody<'_> {
+        if let Some(script) = &self.script {
+            TaskBody::Script {
+                script,
+                script_runner: self
+                    .script_runner
+                    .as_deref()
+                    .unwrap_or(DEFAULT_SCRIPT_RUNNER),
+            }
+        } else if let Some(program) = &self.program {
+            TaskBody::Program {
+                program,
+                args: self.args.as_deref().unwrap_or_default(),
+            }
+        } else if let Some(cmds) = &self.cmds {
+            TaskBody::Cmds {
+                cmds,
+                parallel: self.parallel.unwrap_or(false),
+            }
+        } else {
+            TaskBody::Nothing
+        }
+    }
+
+    /// Returns the commands that run after the task, even if it fails.
+    pub(crate) fn get_finally(&self) -> &[Cmd] {
+        self.finally.as_deref().unwrap_or_default()
+    }
+
+    /// Resolves the task to render its templates.
+    ///
+    /// # Arguments
+    ///
+    /// * `args`: Arguments the task was called with
+    /// * `mom_file`: Mom file the task belongs to
+    /// * `env`: Env of the task, with its dynamic values already computed
+    /// * `vars`: Vars of the task, with its dynamic values already computed
+    /// * `params`: Values of the params of the task
+    fn resolve(
+        &self,
+        args: &ArgsContext,
+        mom_file: &MomFile,
+        env: HashMap<String, String>,
+        vars: HashMap<String, serde_yaml::Value>,
+        params: HashMap<String, tera::Value>,
+    ) -> Result<ResolvedTask, TaskError> {
+        let tera_instance = self.get_tera_instance(mom_file, env.clone())?;
+        let tera_context = self.get_tera_context(args, mom_file, &env, &vars, &params);
+        Ok(ResolvedTask {
+            env,
+            vars,
+            params,
+            tera_instance,
+            tera_context,
+        })
+    }
+
+    /// Resolves the task without running anything, as it is explained. The dynamic env and vars
+    /// are replaced with placeholders, and as no arguments are given, the params take their
+    /// defaults, except the required ones, which are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `mom_file`: Mom file the task belongs to
+    /// * `ctx`: Context of the current run, holding the overrides
+    pub(crate) fn resolve_without_running(
+        &self,
+        mom_file: &MomFile,
+        ctx: &RunContext,
+    ) -> Result<ResolvedTask, TaskError> {
+        let (mut env, mut vars, dynamic) = self.get_env_and_vars(mom_file, ctx);
+        dynamic.set_placeholders(&mut env, &mut vars);
+        let mut params = HashMap::new();
+        for param in self.get_params().iter().filter(|param| !param.required) {
+            let value = param.get_default_value(&mom_file.directory)?;
+            params.insert(param.name.clone(), value);
+        }
+        self.resolve(&ArgsContext::new(), mom_file, env, vars, params)
+    }
+
     // Returns the Tera instance for the Tera template engine.
     fn get_tera_instance(
         &self,
@@ -534,12 +1327,14 @@ impl Task {
         mom_file: &MomFile,
         env: &HashMap<String, String>,
         vars: &HashMap<String, serde_yaml::Value>,
+        params: &HashMap<String, tera::Value>,
     ) -> tera::Context {
         let mut context = tera::Context::new();
 
         context.insert("args", &args.args);
         context.insert("kwargs", &args.kwargs);
         context.insert("pkwargs", &args.pkwargs);
+        context.insert("params", &params);
         context.insert("vars", &vars);
         context.insert("env", &env);
         context.insert("TASK", self);
@@ -566,32 +1361,222 @@ impl Task {
         command.stderr(Stdio::inherit());
         command.stdin(Stdio::inherit());
 
+        if let Some(wd) = self.get_wd(mom_file, env) {
+            command.current_dir(wd);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the working directory of the task, if it has one or the mom file has one.
+    ///
+    /// # Arguments
+    ///
+    /// * `mom_file`: mom file the task belongs to
+    /// * `env`: Environment variables to expand in the working directory
+    pub(crate) fn get_wd(
+        &self,
+        mom_file: &MomFile,
+        env: &HashMap<String, String>,
+    ) -> Option<PathBuf> {
         let wd = match &self.common.wd {
             None => mom_file.common.wd.as_ref(),
             Some(wd) => Some(wd),
         };
-
-        if let Some(wd) = wd {
+        wd.map(|wd| {
             let wd = expand_arg(wd, env);
             let wd = Path::new(wd.as_ref());
-            let mom_file_folder = &mom_file.directory;
             // wd may be absolute or relative to the mom file folder
-            let wd = get_working_directory(mom_file_folder, wd);
-            command.current_dir(wd);
+            get_working_directory(&mom_file.directory, wd)
+        })
+    }
+
+    /// Prints what a command would run in dry mode: its arguments after rendering and expanding
+    /// them, the working directory it would run in, and the environment variables it would set
+    /// on top of the ones of the current process.
+    ///
+    /// # Arguments
+    ///
+    /// * `argv`: Program and arguments of the command
+    /// * `builtin`: Whether the program is a builtin command of mom, which runs in process
+    /// * `mom_file`: mom file the task belongs to
+    /// * `env`: Environment variables of the command
+    fn print_dry_run_plan(
+        &self,
+        argv: &[&str],
+        builtin: bool,
+        mom_file: &MomFile,
+        env: &HashMap<String, String>,
+    ) {
+        let argv = format!("  argv: {:?}", argv);
+        let argv = if builtin {
+            format!("{} (builtin)", argv)
+        } else {
+            argv
+        };
+        println!("{}", argv.mom_info());
+        let wd = match self.get_wd(mom_file, env) {
+            Some(wd) => wd,
+            None => env::current_dir().unwrap_or_default(),
+        };
+        println!("{}", format!("  wd: {}", wd.display()).mom_info());
+        let parent_env: HashMap<String, String> = env::vars_os()
+            .map(|(key, value)| {
+                (
+                    key.to_string_lossy().into_owned(),
+                    value.to_string_lossy().into_owned(),
+                )
+            })
+            .collect();
+        let env_diff = get_env_diff(env, &parent_env);
+        if env_diff.is_empty() {
+            println!("{}", "  env: unchanged".mom_info());
+        } else {
+            println!("{}", "  env:".mom_info());
+            for line in env_diff {
+                println!("{}", format!("    {}", line).mom_info());
+            }
         }
+    }
 
-        Ok(())
+    /// Returns the environment variables and variables of the task, merged with the ones of the
+    /// mom file and the overrides. The dynamic ones are taken apart, as they are only computed
+    /// once they are used.
+    ///
+    /// # Arguments
+    ///
+    /// * `mom_file`: mom file the task belongs to
+    /// * `ctx`: Context of the current run, holding the overrides
+    fn get_env_and_vars(&self, mom_file: &MomFile, ctx: &RunContext) -> EnvAndVars {
+        let mut env = self.get_env(&mom_file.common.env);
+        let mut vars = self.get_vars(&mom_file.common.vars);
+        ctx.overrides.apply(&mut env, &mut vars);
+
+        let wd = self.common.wd.as_ref().or(mom_file.common.wd.as_ref());
+        let is_declared = |key: &str| self.get_vars_origin(key, mom_file, ctx).is_declared();
+        let (env, dynamic) = DynamicValues::take(
+            env,
+            &mut vars,
+            is_declared,
+            wd.map(String::as_str),
+            &mom_file.directory,
+        );
+        (env, vars, dynamic)
+    }
+
+    /// Returns where the env value with the given key comes from, once merged with the ones of
+    /// the mom file and the overrides.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: Name of the env value
+    /// * `mom_file`: mom file the task belongs to
+    /// * `ctx`: Context of the current run, holding the overrides
+    pub(crate) fn get_env_origin(&self, key: &str, mom_file: &MomFile, ctx: &RunContext) -> Origin {
+        if ctx.overrides.env.contains_key(key) {
+            Origin::Override
+        } else if self.common.env.contains_key(key) {
+            self.common.get_env_origin(key)
+        } else {
+            Origin::File(
+                mom_file.filepath.clone(),
+                Box::new(mom_file.common.get_env_origin(key)),
+            )
+        }
+    }
+
+    /// Returns where the variable with the given key comes from, once merged with the ones of
+    /// the mom file and the overrides.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: Name of the variable
+    /// * `mom_file`: mom file the task belongs to
+    /// * `ctx`: Context of the current run, holding the overrides
+    pub(crate) fn get_vars_origin(
+        &self,
+        key: &str,
+        mom_file: &MomFile,
+        ctx: &RunContext,
+    ) -> Origin {
+        if ctx.overrides.vars.contains_key(key) {
+            Origin::Override
+        } else if self.common.vars.contains_key(key) {
+            self.common.get_vars_origin(key)
+        } else {
+            Origin::File(
+                mom_file.filepath.clone(),
+                Box::new(mom_file.common.get_vars_origin(key)),
+            )
+        }
+    }
+
+    /// Returns the templates of the task, which decide the dynamic vars it uses. The ones of
+    /// the tasks it runs from its commands are left out, as they compute their own.
+    ///
+    /// # Arguments
+    ///
+    /// * `mom_file`: mom file the task belongs to, with the `incl` templates it can use
+    fn get_templates<'a>(&'a self, mom_file: &'a MomFile) -> Vec<&'a str> {
+        fn add_cmds<'a>(cmds: &'a [Cmd], templates: &mut Vec<&'a str>) {
+            for cmd in cmds {
+                match cmd {
+                    Cmd::Cmd(cmd) => templates.push(cmd),
+                    Cmd::Inline(inline) => templates.push(&inline.cmd),
+                    Cmd::Parallel(cmds) => add_cmds(cmds, templates),
+                    Cmd::Task(_) | Cmd::TaskName(_) => {}
+                }
+            }
+        }
+
+        let mut templates: Vec<&str> = self.get_incl_templates(mom_file);
+        let fields = [
+            &self.script,
+            &self.script_runner,
+            &self.program,
+            &self.args,
+            &self.args_extend,
+        ];
+        templates.extend(fields.into_iter().flatten().map(String::as_str));
+        for cmds in [&self.cmds, &self.finally].into_iter().flatten() {
+            add_cmds(cmds, &mut templates);
+        }
+        templates
+    }
+
+    /// Returns the `incl` templates the task can use, from the mom file and the task itself.
+    fn get_incl_templates<'a>(&'a self, mom_file: &'a MomFile) -> Vec<&'a str> {
+        let incl = mom_file
+            .common
+            .incl
+            .values()
+            .chain(self.common.incl.values());
+        incl.map(String::as_str).collect()
     }
 
-    /// Spawns a command and waits for its execution.
+    /// Spawns a command and waits for its execution. Returns the output of the command if its
+    /// stdout was piped, i.e. to register it, or an empty string otherwise.
     ///
     /// # Arguments
     ///
     /// * `command` - Command to spawn
-    fn spawn_command(&self, command: &mut Command, dry_run: bool) -> Result<(), TaskError> {
-        if dry_run {
-            println!("{}", DRY_RUN_MESSAGE.mom_info());
-            return Ok(());
+    /// * `options` - Options of the command, which decide the exit codes that are successful
+    /// * `ctx` - Context of the current run. The child is killed if it gets cancelled
+    fn spawn_command(
+        &self,
+        command: &mut Command,
+        options: &CommandOptions,
+        ctx: &RunContext,
+    ) -> Result<String, TaskError> {
+        ctx.check_can_continue()?;
+        let isolated = ctx.isolated;
+        if isolated {
+            isolate_command(command);
+            // Out of the foreground process group, reading from the terminal would stop the
+            // child with SIGTTIN, so it reads an empty input instead
+            if cfg!(unix) && io::stdin().is_terminal() {
+                command.stdin(Stdio::null());
+            }
         }
         let mut child = match command.spawn() {
             Ok(child) => child,
@@ -600,58 +1585,164 @@ impl Task {
             }
         };
 
+        // Read in another thread, so that the child does not block once the pipe is full
+        let stdout_reader = child.stdout.take().map(|mut stdout| {
+            thread::spawn(move || {
+                let mut output = Vec::new();
+                stdout.read_to_end(&mut output).map(|_| output)
+            })
+        });
+
         // let child handle ctrl-c to prevent dropping the parent and leaving the child running
-        ctrlc::set_handler(move || {}).unwrap_or(());
-
-        let result = child.wait()?;
-        match result.success() {
-            true => Ok(()),
-            false => match result.code() {
-                None => Err(TaskError::RuntimeError(String::from(
-                    "Process did not terminate correctly",
-                ))),
-                Some(code) => Err(TaskError::RuntimeError(format!(
-                    "Process terminated with exit code {}",
-                    code
-                ))),
-            },
+        set_interrupt_handler();
+
+        // Polls instead of blocking on `wait`, so that the child can be killed if the run is
+        // cancelled, i.e. when a parallel command fails.
+        let mut interrupted = false;
+        let result = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if isolated && !interrupted && ctx.is_interrupted() {
+                interrupt_child(&child);
+                interrupted = true;
+            }
+            if ctx.is_cancelled() {
+                kill_child(&mut child, isolated);
+                child.wait()?;
+                return Err(TaskError::RuntimeError(String::from(
+                    "Process was cancelled",
+                )));
+            }
+            if let Some(deadline) = ctx.deadline.as_ref().filter(|d| d.has_expired()) {
+                terminate_child(&mut child, isolated)?;
+                return Err(deadline.error());
+            }
+            thread::sleep(CHILD_POLL_INTERVAL);
+        };
+        let output = match stdout_reader {
+            Some(reader) => reader
+                .join()
+                .map_err(|_| TaskError::RuntimeError(String::from("Error reading the output")))??,
+            None => Vec::new(),
+        };
+        match result.code() {
+            Some(code) if options.is_ok_exit_code(code) => {
+                Ok(String::from_utf8_lossy(&output).into_owned())
+            }
+            code => Err(TaskError::ProcessFailed {
+                code,
+                signal: get_exit_signal(&result),
+            }),
+        }
+    }
+
+    /// Runs a command, running it again after a delay if it fails, as many times as the
+    /// `retries` option allows. Each run gets its own `timeout`, if set. Errors caused by the
+    /// run being interrupted, cancelled or reaching the timeout of the task are not retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - Function that runs the command once with the given context
+    /// * `display_name` - Name the command is shown with, i.e. `task.cmds.0`
+    /// * `options` - Options of the command
+    /// * `ctx` - Context of the current run
+    fn run_with_retries<T, F>(
+        mut run: F,
+        display_name: &str,
+        options: &CommandOptions,
+        ctx: &RunContext,
+    ) -> Result<T, TaskError>
+    where
+        F: FnMut(&RunContext) -> Result<T, TaskError>,
+    {
+        let retries = options.get_retries();
+        let mut retry = 0;
+        loop {
+            let result = match options.timeout {
+                Some(timeout) => run(&ctx.with_timeout(display_name, timeout)),
+                None => run(ctx),
+            };
+            match result {
+                Err(e) if retry < retries && ctx.check_can_continue().is_ok() => {
+                    let delay = options.get_retry_delay(retry);
+                    retry += 1;
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "{display_name} failed, retrying in {} ({retry}/{retries}):\n{e}",
+                            DurationValue(delay)
+                        )
+                        .mom_warn()
+                    );
+                    ctx.sleep(delay)?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Reports the error of a failed command as a warning if the command ignores errors,
+    /// instead of returning it, in which case the default value is returned. Errors caused by
+    /// the run being interrupted or cancelled are always returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - Result of running the command
+    /// * `display_name` - Name the command is shown with, i.e. `task.cmds.0`
+    /// * `options` - Options of the command
+    /// * `ctx` - Context of the current run
+    fn check_command_result<T: Default>(
+        result: Result<T, TaskError>,
+        display_name: &str,
+        options: &CommandOptions,
+        ctx: &RunContext,
+    ) -> Result<T, TaskError> {
+        match result {
+            Err(e) if options.ignores_error() && ctx.check_can_continue().is_ok() => {
+                eprintln!(
+                    "{}",
+                    format!("{display_name} failed, ignoring the error:\n{e}").mom_warn()
+                );
+                Ok(T::default())
+            }
+            result => result,
         }
     }
 
     /// Runs a program
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - Program to run
+    /// * `args` - Template of the arguments of the program
+    /// * `mom_file` - Mom file the task belongs to
+    /// * `resolved` - Task resolved to render its templates
+    /// * `ctx` - Context of the current run
     fn run_program(
         &self,
+        program: &str,
+        args: &str,
         mom_file: &MomFile,
-        env: &HashMap<String, String>,
-        tera_instance: &mut tera::Tera,
-        tera_context: &mut tera::Context,
-        dry_mode: bool,
+        resolved: &mut ResolvedTask,
+        ctx: &RunContext,
     ) -> Result<(), TaskError> {
-        let program = self.program.as_ref().unwrap();
-
+        let env = &resolved.env;
         // In case the program is specified with ~ or $HOME, or something like that
         let program = expand_arg(program, env);
 
         let mut command = Command::new(program.as_ref());
         self.set_command_basics(&mut command, mom_file, env)?;
 
-        let args_list = match &self.args {
-            None => vec![],
-            Some(args) => {
-                let task_name = &self.name;
-                let template_name = format!("tasks.{task_name}.args");
-                tera_instance.add_raw_template(&template_name, args)?;
-                let rendered_args = tera_instance.render(&template_name, tera_context)?;
-                split_command(&rendered_args)
-            }
-        };
+        let args_list = split_command(&resolved.render(&self.name, "args", args)?);
+        let env = &resolved.env;
+        let args = expand_args(&args_list, env);
+        let args: Vec<&str> = args.iter().map(|s| s.as_ref()).collect();
         if args_list.is_empty() {
             println!("{}", format!("{}: {}", self.name, program).mom_info());
         } else {
             let display_args = join_commands(&args_list);
-            let args = expand_args(&args_list, env);
-            let args = args.iter().map(|s| s.as_ref());
-            command.args(args);
+            command.args(&args);
 
             println!(
                 "{}",
@@ -659,28 +1750,47 @@ impl Task {
             );
         }
 
-        self.spawn_command(&mut command, dry_mode)
+        if ctx.dry_run {
+            let argv: Vec<&str> = std::iter::once(program.as_ref()).chain(args).collect();
+            self.print_dry_run_plan(&argv, false, mom_file, env);
+            return Ok(());
+        }
+
+        let options = self.get_command_options();
+        let result = Self::run_with_retries(
+            |ctx| self.spawn_command(&mut command, &options, ctx),
+            &self.name,
+            &options,
+            ctx,
+        );
+        Self::check_command_result(result, &self.name, &options, ctx).map(|_| ())
     }
 
-    #[allow(clippy::too_many_arguments)]
+    /// Runs a command of the cmds option, with the options of the task for the ones it does not
+    /// set.
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - Command to run, along with its options
+    /// * `cmd_path` - Option and index of the command, i.e. `cmds.2`
+    /// * `mom_file` - Mom file the task belongs to
+    /// * `resolved` - Task resolved to render its templates
+    /// * `ctx` - Context of the current run
     fn run_cmds_cmd(
         &self,
-        cmd: &str,
-        cmd_index: usize,
+        cmd: &InlineCmd,
+        cmd_path: &str,
         mom_file: &MomFile,
-        env: &HashMap<String, String>,
-        tera_instance: &mut tera::Tera,
-        tera_context: &mut tera::Context,
-        dry_run: bool,
+        resolved: &mut ResolvedTask,
+        ctx: &RunContext,
     ) -> Result<(), TaskError> {
+        let register = cmd.register.as_deref();
+        let options = &cmd.options.with_defaults(&self.get_command_options());
         let task_name = &self.name;
-        let task_name = &format!("{task_name}.cmds.{cmd_index}");
-        let template_name = &format!("tasks.{task_name}");
-        tera_instance.add_raw_template(template_name, cmd)?;
-
-        let cmd = tera_instance.render(template_name, tera_context);
-        let cmd = cmd?;
-        let cmd_args = split_command(&cmd);
+        let task_name = &format!("{task_name}.{cmd_path}");
+        let rendered = resolved.render(&self.name, cmd_path, &cmd.cmd)?;
+        let env = &resolved.env;
+        let cmd_args = split_command(&rendered);
         let cmd_args: Vec<Cow<str>> = expand_args(&cmd_args, env);
         let cmd_args: Vec<&str> = cmd_args.iter().map(|s| s.as_ref()).collect();
         let program = match cmd_args.first() {
@@ -694,65 +1804,113 @@ impl Task {
         };
         // We print the clean commands, not the rendered ones. For a nicer output.
         let command_info = format!("{task_name}: {}", join_commands(&cmd_args)).mom_info();
+        let builtin_command = get_builtin_command(program);
+
+        if ctx.dry_run {
+            println!("{}", command_info);
+            self.print_dry_run_plan(&cmd_args, builtin_command.is_some(), mom_file, env);
+            // The output is not known, but the next commands can still be rendered
+            if let Some(register) = register {
+                resolved.register_output(register, "");
+            }
+            return Ok(());
+        }
 
-        // We need to do some refactoring, dry_run should not be dependent on the Command instance.
-        if !dry_run {
-            if let Some(builtin_command) = get_builtin_command(program) {
-                println!("{}", command_info);
-                return builtin_command(&cmd_args[1..])
-                    .map_err(|e| TaskError::RuntimeError(format!("Error running task: {}", e)));
+        if let Some(builtin_command) = builtin_command {
+            println!("{}", command_info);
+            let result = Self::run_with_retries(
+                |_| {
+                    let mut output = Vec::new();
+                    let mut stdout = io::stdout();
+                    let out: &mut dyn Write = match register {
+                        Some(_) => &mut output,
+                        None => &mut stdout,
+                    };
+                    builtin_command(&cmd_args[1..], out).map_err(|e| {
+                        TaskError::RuntimeError(format!("Error running task: {}", e))
+                    })?;
+                    Ok(String::from_utf8_lossy(&output).into_owned())
+                },
+                task_name,
+                options,
+                ctx,
+            );
+            let output = Self::check_command_result(result, task_name, options, ctx)?;
+            if let Some(register) = register {
+                resolved.register_output(register, &output);
             }
+            return Ok(());
         }
         let program_args = &cmd_args[1..];
         let mut command: Command = Command::new(program);
         self.set_command_basics(&mut command, mom_file, env)?;
         command.args(program_args);
+        if register.is_some() {
+            command.stdout(Stdio::piped());
+        }
 
         println!("{}", command_info);
-        self.spawn_command(&mut command, dry_run)
+        let result = Self::run_with_retries(
+            |ctx| self.spawn_command(&mut command, options, ctx),
+            task_name,
+            options,
+            ctx,
+        );
+        let output = Self::check_command_result(result, task_name, options, ctx)?;
+        if let Some(register) = register {
+            resolved.register_output(register, &output);
+        }
+        Ok(())
     }
 
-    fn run_cmds_task_name(
+    /// Returns the task called by name from the commands of this one, merged with this one,
+    /// along with the mom file it belongs to.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_name`: Name of the task, possibly namespaced
+    /// * `cmd_path`: Path of the command calling the task, i.e. `cmds.2`
+    /// * `mom_file`: Mom file this task belongs to
+    pub(crate) fn get_cmds_task_by_name<'a>(
         &self,
         task_name: &str,
-        cmd_index: usize,
-        args: &ArgsContext,
-        mom_file: &MomFile,
-        dry_run: bool,
-    ) -> Result<(), TaskError> {
-        let display_task_name = format!("{}.cmds.{}.{}", self.name, cmd_index, task_name);
-        if let Some(mut task) = mom_file.clone_task(task_name) {
+        cmd_path: &str,
+        mom_file: &'a MomFile,
+    ) -> Result<(&'a MomFile, Task), TaskError> {
+        let display_task_name = format!("{}.{}.{}", self.name, cmd_path, task_name);
+        // Tasks of included files run with the file they belong to
+        let task = mom_file
+            .resolve_namespace(task_name)
+            .and_then(|(mom_file, name)| Some((mom_file, name, mom_file.clone_task(name)?)));
+        if let Some((mom_file, name, mut task)) = task {
             // The env and vars of the parent take precedence in this case.
-            task.common.env = self.get_env(&task.common.env);
-            task.common.vars = self.get_vars(&task.common.vars);
-            task.common.incl = self.get_templates(&task.common.incl);
+            task.common.merge_parent(&self.common, &self.name, true);
 
             // Should setup first, to load the env_file.
             task.setup(&display_task_name, &mom_file.directory)?;
-
-            if let Err(e) = task.run(args, mom_file, dry_run) {
-                Err(TaskError::RuntimeError(format!(
-                    "Error running task: {}",
-                    e
-                )))
-            } else {
-                Ok(())
-            }
+            task.defined_name = Some(String::from(name));
+            Ok((mom_file, task))
         } else {
             Err(TaskError::NotFound(task_name.to_string()))
         }
     }
 
-    fn run_cmds_task(
+    /// Returns the task given in the commands of this one, extended from its bases and merged
+    /// with this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `task`: Task given in the commands
+    /// * `cmd_path`: Path of the command the task is given in, i.e. `cmds.2`
+    /// * `mom_file`: Mom file this task belongs to
+    pub(crate) fn get_cmds_task(
         &self,
         task: &Task,
-        cmd_index: usize,
-        args: &ArgsContext,
+        cmd_path: &str,
         mom_file: &MomFile,
-        dry_run: bool,
-    ) -> Result<(), TaskError> {
+    ) -> Result<Task, TaskError> {
         let mut task = task.clone();
-        let task_name = format!("{}.cmds.{}", self.name, cmd_index);
+        let task_name = format!("{}.{}", self.name, cmd_path);
 
         task.setup(&task_name, &mom_file.directory)?;
 
@@ -774,55 +1932,242 @@ impl Task {
         }
 
         // Done after setup and bases, so that the env and vars specified directly in the child take precedence
-        task.common.env = task.get_env(&self.common.env);
-        task.common.vars = task.get_vars(&self.common.vars);
-        task.common.incl = task.get_templates(&self.common.incl);
+        task.common.merge_parent(&self.common, &self.name, false);
+        Ok(task)
+    }
+
+    fn run_cmds_task_name(
+        &self,
+        task_name: &str,
+        cmd_path: &str,
+        args: &ArgsContext,
+        mom_file: &MomFile,
+        ctx: &RunContext,
+    ) -> Result<(), TaskError> {
+        let (mom_file, task) = self.get_cmds_task_by_name(task_name, cmd_path, mom_file)?;
+        task.run(args, mom_file, ctx).map_err(|e| e.into())
+    }
 
+    fn run_cmds_task(
+        &self,
+        task: &Task,
+        cmd_path: &str,
+        args: &ArgsContext,
+        mom_file: &MomFile,
+        ctx: &RunContext,
+    ) -> Result<(), TaskError> {
+        let task = self.get_cmds_task(task, cmd_path, mom_file)?;
         // This should load the mom file env and vars
-        task.run(args, mom_file, dry_run).map_err(|e| e.into())
+        task.run(args, mom_file, ctx).map_err(|e| e.into())
     }
 
-    /// Runs the commands specified with the cmds option.
+    /// Runs a single entry of the cmds option.
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd_path`: Option and index of the command, followed by the index inside the parallel
+    ///   group it belongs to, if any, i.e. `cmds.2.1`
+    fn run_cmd(
+        &self,
+        cmd: &Cmd,
+        cmd_path: &str,
+        args: &ArgsContext,
+        mom_file: &MomFile,
+        resolved: &mut ResolvedTask,
+        ctx: &RunContext,
+    ) -> Result<(), TaskError> {
+        match cmd {
+            Cmd::Cmd(cmd) => {
+                let cmd = InlineCmd {
+                    cmd: cmd.clone(),
+                    register: None,
+                    options: CommandOptions::default(),
+                };
+                self.run_cmds_cmd(&cmd, cmd_path, mom_file, resolved, ctx)
+            }
+            Cmd::Inline(inline) => self.run_cmds_cmd(inline, cmd_path, mom_file, resolved, ctx),
+            Cmd::TaskName(task_name) => {
+                self.run_cmds_task_name(task_name, cmd_path, args, mom_file, ctx)
+            }
+            Cmd::Task(task) => self.run_cmds_task(task, cmd_path, args, mom_file, ctx),
+            Cmd::Parallel(cmds) => {
+                self.run_cmds_parallel(cmds, cmd_path, args, mom_file, resolved, ctx)
+            }
+        }
+    }
+
+    /// Runs the given commands one after the other.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix`: Path the index of each command is appended to, i.e. `cmds`
     fn run_cmds(
         &self,
+        cmds: &[Cmd],
+        prefix: &str,
         args: &ArgsContext,
         mom_file: &MomFile,
-        env: &HashMap<String, String>,
-        tera_instance: &mut tera::Tera,
-        tera_context: &mut tera::Context,
-        dry_run: bool,
+        resolved: &mut ResolvedTask,
+        ctx: &RunContext,
     ) -> Result<(), TaskError> {
-        for (i, cmd) in self.cmds.as_ref().unwrap().iter().enumerate() {
-            match cmd {
-                Cmd::Cmd(cmd) => {
-                    self.run_cmds_cmd(cmd, i, mom_file, env, tera_instance, tera_context, dry_run)?;
-                }
-                Cmd::TaskName(task_name) => {
-                    self.run_cmds_task_name(task_name, i, args, mom_file, dry_run)?;
-                }
-                Cmd::Task(task) => {
-                    self.run_cmds_task(task, i, args, mom_file, dry_run)?;
-                }
+        for (i, cmd) in cmds.iter().enumerate() {
+            ctx.check_can_continue()?;
+            self.run_cmd(cmd, &format!("{prefix}.{i}"), args, mom_file, resolved, ctx)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the given commands concurrently, with at most `jobs` commands running at the same
+    /// time. If `fail_fast` is not disabled, the remaining commands are cancelled as soon as one
+    /// fails, otherwise all of them run and the errors are reported together.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix`: Path the index of each command is appended to, i.e. `cmds` or the path of
+    ///   the parallel group
+    fn run_cmds_parallel(
+        &self,
+        cmds: &[Cmd],
+        prefix: &str,
+        args: &ArgsContext,
+        mom_file: &MomFile,
+        resolved: &ResolvedTask,
+        ctx: &RunContext,
+    ) -> Result<(), TaskError> {
+        ctx.run_parallel(
+            cmds.len(),
+            self.jobs.unwrap_or(cmds.len()),
+            self.fail_fast.unwrap_or(true),
+            |i, ctx| {
+                let cmd_path = format!("{prefix}.{i}");
+                // Templates are added while rendering, so each command needs its own copy
+                self.run_cmd(
+                    &cmds[i],
+                    &cmd_path,
+                    args,
+                    mom_file,
+                    &mut resolved.clone(),
+                    ctx,
+                )
+            },
+        )
+    }
+
+    /// Runs the tasks declared in `deps`, as well as the tasks they depend on, in topological
+    /// order. Each dependency runs at most once per invocation, even if multiple tasks depend on
+    /// it. If `parallel` is set, the dependencies that do not depend on each other run
+    /// concurrently. The tasks of included files, given with their namespace, run first, as they
+    /// cannot depend on the tasks of this file.
+    fn run_deps(&self, mom_file: &MomFile, ctx: &RunContext) -> Result<(), TaskError> {
+        if self.deps.is_empty() {
+            return Ok(());
+        }
+
+        let graph = get_task_deps_graph(&mom_file.tasks)
+            .map_err(|e| TaskError::ConfigError(e.to_string()))?;
+
+        // Tasks of included files run with the file they belong to
+        let get_dep = |name: &str| -> Result<(&MomFile, &Task), TaskError> {
+            mom_file
+                .resolve_namespace(name)
+                .and_then(|(mom_file, name)| Some((mom_file, mom_file.get_task(name)?)))
+                .ok_or_else(|| TaskError::NotFound(name.to_string()))
+        };
+
+        // Only the dependencies reachable from this task need to run
+        let mut included_deps: Vec<&str> = Vec::new();
+        let mut needed: HashSet<&str> = HashSet::new();
+        for dep_name in self.deps.iter() {
+            let (_, dep) = get_dep(dep_name)?;
+            if dep_name.contains(':') {
+                included_deps.push(dep_name);
+                continue;
+            }
+            let mut dfs = Dfs::new(&graph, dep.get_name());
+            while let Some(name) = dfs.next(&graph) {
+                needed.insert(name);
+            }
+        }
+
+        // Cycles are detected when loading the mom file, so this should not fail
+        let sorted = toposort(&graph, None).map_err(|e| {
+            let cycle = find_cycle(&graph, e.node_id()).unwrap_or(vec![e.node_id()]);
+            TaskError::ConfigError(format!(
+                "Found a cyclic dependency for task: {}",
+                cycle.join(" -> ")
+            ))
+        })?;
+        // Edges go from the task to its dependencies, so dependencies come last
+        let sorted: Vec<&str> = sorted
+            .into_iter()
+            .rev()
+            .filter(|name| needed.contains(name))
+            .collect();
+
+        let run_dep = |name: &str, ctx: &RunContext| -> Result<(), TaskError> {
+            let (mom_file, dep) = get_dep(name)?;
+            let key = format!("{}:{}", mom_file.filepath.display(), dep.get_name());
+            ctx.run_once(&key, || dep.run(&ArgsContext::new(), mom_file, ctx))
+                .map_err(|e| e.into())
+        };
+
+        if !self.parallel.unwrap_or(false) {
+            for name in included_deps.into_iter().chain(sorted) {
+                ctx.check_can_continue()?;
+                run_dep(name, ctx)?;
+            }
+            return Ok(());
+        }
+
+        // Groups the dependencies in levels, where each level only depends on the previous ones
+        let mut levels: Vec<Vec<&str>> = Vec::new();
+        if !included_deps.is_empty() {
+            levels.push(included_deps);
+        }
+        let mut task_levels: HashMap<&str, usize> = HashMap::new();
+        for name in sorted {
+            let level = graph
+                .neighbors(name)
+                .map(|dep_name| task_levels[dep_name] + 1)
+                .max()
+                .unwrap_or(0);
+            task_levels.insert(name, level);
+            if levels.len() <= level {
+                levels.push(Vec::new());
             }
+            levels[level].push(name);
+        }
+
+        for level in levels {
+            ctx.run_parallel(
+                level.len(),
+                self.jobs.unwrap_or(level.len()),
+                self.fail_fast.unwrap_or(true),
+                |i, ctx| run_dep(level[i], ctx),
+            )?;
         }
         Ok(())
     }
 
     /// Runs a script
+    ///
+    /// # Arguments
+    ///
+    /// * `script` - Template of the script
+    /// * `script_runner` - Template of the command that runs the script
+    /// * `mom_file` - Mom file the task belongs to
+    /// * `resolved` - Task resolved to render its templates
+    /// * `ctx` - Context of the current run
     fn run_script(
         &self,
+        script: &str,
+        script_runner: &str,
         mom_file: &MomFile,
-        env: &HashMap<String, String>,
-        tera_instance: &mut tera::Tera,
-        tera_context: &mut tera::Context,
-        dry_run: bool,
+        resolved: &mut ResolvedTask,
+        ctx: &RunContext,
     ) -> Result<(), TaskError> {
-        let script = self.script.as_ref().unwrap();
-
         let task_name = &self.name;
-        let template_name = format!("tasks.{task_name}.script");
-        tera_instance.add_raw_template(&template_name, script)?;
-        let script = tera_instance.render(&template_name, tera_context)?;
+        let script = resolved.render(task_name, "script", script)?;
         let default_script_extension = String::from(DEFAULT_SCRIPT_EXTENSION);
         let script_extension = self
             .script_extension
@@ -851,24 +2196,16 @@ impl Task {
             {
                 let script_path = script_path.to_str().unwrap();
                 let script_path = script_path.replace('\\', "\\\\");
-                tera_context.insert("script_path", &script_path);
+                resolved.insert("script_path", &script_path);
             } else {
-                tera_context.insert("script_path", &script_path);
+                resolved.insert("script_path", &script_path);
             }
         }
 
         // Interpreter is a list, because sometimes there is need to pass extra arguments to the
         // interpreter, such as the /C option in the batch case
-        let script_runner = if let Some(script_runner) = &self.script_runner {
-            script_runner
-        } else {
-            DEFAULT_SCRIPT_RUNNER
-        };
-
-        let script_runner_template_name = format!("tasks.{task_name}.script_runner");
-        tera_instance.add_raw_template(&script_runner_template_name, script_runner)?;
-
-        let script_runner = tera_instance.render(&script_runner_template_name, tera_context)?;
+        let script_runner = resolved.render(task_name, "script_runner", script_runner)?;
+        let env = &resolved.env;
         let script_runner_values = split_command(&script_runner);
         let script_runner_values = expand_args(&script_runner_values, env);
         let script_runner_values: Vec<&str> =
@@ -888,6 +2225,18 @@ impl Task {
         println!("{}", script.color(INFO_COLOR));
         println!("{}", "Script End.".mom_info());
 
-        self.spawn_command(&mut command, dry_run)
+        if ctx.dry_run {
+            self.print_dry_run_plan(&script_runner_values, false, mom_file, env);
+            return Ok(());
+        }
+
+        let options = self.get_command_options();
+        let result = Self::run_with_retries(
+            |ctx| self.spawn_command(&mut command, &options, ctx),
+            task_name,
+            &options,
+            ctx,
+        );
+        Self::check_command_result(result, task_name, &options, ctx).map(|_| ())
     }
 }
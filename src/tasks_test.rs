@@ -1,4 +1,5 @@
 use super::*;
+use crate::dynamic_values::EnvValue;
 use crate::errors::{AwareTaskError, TaskError};
 use crate::mom_files::MomFile;
 use assert_fs::TempDir;
@@ -7,6 +8,7 @@ use std::fs;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
+use std::time::Duration;
 
 pub(crate) fn get_task(
     name: &str,
@@ -53,8 +55,8 @@ tasks:
     let task = mom_file.clone_task("hello").unwrap();
 
     let expected = HashMap::from([
-        ("greeting".to_string(), "hello world".to_string()),
-        ("one_plus_one".to_string(), "2".to_string()),
+        ("greeting".to_string(), EnvValue::from("hello world")),
+        ("one_plus_one".to_string(), EnvValue::from("2")),
     ]);
     assert_eq!(task.common.env, expected);
 }
@@ -217,18 +219,18 @@ tasks:
     let env = task.get_env(&mom_file.common.env);
 
     let expected = HashMap::from([
-        ("VAR1".to_string(), "VAL1".to_string()),
-        ("VAR2".to_string(), "VAL2".to_string()),
-        ("VAR3".to_string(), "VAL3".to_string()),
+        ("VAR1".to_string(), EnvValue::from("VAL1")),
+        ("VAR2".to_string(), EnvValue::from("VAL2")),
+        ("VAR3".to_string(), EnvValue::from("VAL3")),
     ]);
     assert_eq!(env, expected);
 
     let task = mom_file.clone_task("test_2").unwrap();
     let env = task.get_env(&mom_file.common.env);
     let expected = HashMap::from([
-        ("VAR1".to_string(), "TASK_VAL1".to_string()),
-        ("VAR2".to_string(), "OTHER_VAL2".to_string()),
-        ("VAR3".to_string(), "VAL3".to_string()),
+        ("VAR1".to_string(), EnvValue::from("TASK_VAL1")),
+        ("VAR2".to_string(), EnvValue::from("OTHER_VAL2")),
+        ("VAR3".to_string(), EnvValue::from("VAL3")),
     ]);
     assert_eq!(env, expected);
 }
@@ -338,7 +340,46 @@ fn test_deserialize_cmds() {
     );
     assert_eq!(
         task.unwrap_err().to_string(),
-        "cmds[0]: unknown field `unknown`, expected `task` or `cmd` at line 3 column 15"
+        "cmds[0]: unknown field `unknown`, expected one of `task`, `cmd`, `parallel`, `ignore_error`, `ok_exit_codes`, `retries`, `retry_delay`, `retry_backoff`, `timeout`, `register` at line 3 column 15"
+    );
+
+    let task = get_task(
+        "sample",
+        r#"
+        cmds:
+            - {task: "something", retries: 2}
+    "#,
+        None,
+    );
+    assert_eq!(
+        task.unwrap_err().to_string(),
+        "cmds[0]: `task` cannot be combined with `retries` at line 3 column 15"
+    );
+
+    let task = get_task(
+        "sample",
+        r#"
+        cmds:
+            - {retries: 2, task: "something"}
+    "#,
+        None,
+    );
+    assert_eq!(
+        task.unwrap_err().to_string(),
+        "cmds[0]: `task` cannot be combined with `retries` at line 3 column 15"
+    );
+
+    let task = get_task(
+        "sample",
+        r#"
+        cmds:
+            - {parallel: ["something"], cmd: "something else"}
+    "#,
+        None,
+    );
+    assert_eq!(
+        task.unwrap_err().to_string(),
+        "cmds[0]: `parallel` cannot be combined with `cmd` at line 3 column 15"
     );
 
     let task = get_task(
@@ -378,3 +419,176 @@ fn test_deserialize_cmds() {
         _ => panic!("Expected Cmd::Task"),
     }
 }
+
+#[test]
+fn test_deserialize_cmd_options() {
+    let task = get_task(
+        "sample",
+        r#"
+        ignore_error: true
+        cmds:
+            - cmd: "something1"
+            - ok_exit_codes: [0, 1]
+              cmd: "something2"
+            - {cmd: "something3", ignore_error: false}
+            - cmd: "something4"
+              retries: 3
+              retry_delay: 500ms
+              retry_backoff: 2
+              timeout: 5m
+            - cmd: "something5"
+              register: output
+    "#,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(task.ignore_error, Some(true));
+    let cmds = task.cmds.unwrap();
+
+    match &cmds[0] {
+        Cmd::Cmd(cmd) => assert_eq!(cmd, "something1"),
+        _ => panic!("Expected Cmd::Cmd"),
+    }
+
+    match &cmds[1] {
+        Cmd::Inline(inline) => {
+            assert_eq!(inline.cmd, "something2");
+            assert_eq!(inline.options.ok_exit_codes, Some(vec![0, 1]));
+            assert_eq!(inline.options.ignore_error, None);
+        }
+        _ => panic!("Expected Cmd::Inline"),
+    }
+
+    match &cmds[2] {
+        Cmd::Inline(inline) => {
+            assert_eq!(inline.cmd, "something3");
+            assert_eq!(inline.options.ignore_error, Some(false));
+        }
+        _ => panic!("Expected Cmd::Inline"),
+    }
+
+    match &cmds[3] {
+        Cmd::Inline(inline) => {
+            assert_eq!(inline.options.retries, Some(3));
+            assert_eq!(
+                inline.options.retry_delay,
+                Some(DurationValue(Duration::from_millis(500)))
+            );
+            assert_eq!(inline.options.retry_backoff, Some(2.0));
+            assert_eq!(
+                inline.options.timeout,
+                Some(DurationValue(Duration::from_secs(300)))
+            );
+            assert_eq!(inline.register, None);
+        }
+        _ => panic!("Expected Cmd::Inline"),
+    }
+
+    match &cmds[4] {
+        Cmd::Inline(inline) => {
+            assert_eq!(inline.cmd, "something5");
+            assert_eq!(inline.register.as_deref(), Some("output"));
+            assert!(inline.options.is_empty());
+        }
+        _ => panic!("Expected Cmd::Inline"),
+    }
+
+    let task = get_task(
+        "sample",
+        r#"
+        cmds:
+            - ignore_error: true
+    "#,
+        None,
+    );
+    assert_eq!(
+        task.unwrap_err().to_string(),
+        "cmds[0]: missing field `cmd` at line 3 column 15"
+    );
+
+    let task = get_task(
+        "sample",
+        r#"
+        cmds:
+            - {cmd: "something", retry_delay: 1d}
+    "#,
+        None,
+    );
+    assert_eq!(
+        task.unwrap_err().to_string(),
+        "cmds[0]: Invalid duration `1d`, expected a number followed by ms, s, m or h, i.e. `30s`. at line 3 column 15"
+    );
+
+    let task = get_task(
+        "sample",
+        r#"
+        retry_backoff: 0
+        cmds:
+            - "something"
+    "#,
+        None,
+    );
+    let expected_error = AwareTaskError::new(
+        "sample",
+        TaskError::ConfigError(String::from(
+            "Invalid retry_backoff `0`, expected a number greater than or equal to 1.",
+        )),
+    );
+    assert_eq!(task.unwrap_err().to_string(), expected_error.to_string());
+}
+
+#[test]
+fn test_deserialize_parallel_cmds() {
+    let task = get_task(
+        "sample",
+        r#"
+        parallel: true
+        jobs: 2
+        fail_fast: false
+        cmds:
+            - "something1"
+            - parallel:
+                - "something2"
+                - task: "something3"
+    "#,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(task.parallel, Some(true));
+    assert_eq!(task.jobs, Some(2));
+    assert_eq!(task.fail_fast, Some(false));
+    assert_eq!(task.get_dependencies(), vec!["something3"]);
+
+    let cmds = task.cmds.unwrap();
+    match &cmds[1] {
+        Cmd::Parallel(group) => {
+            assert_eq!(group.len(), 2);
+            match &group[0] {
+                Cmd::Cmd(cmd) => assert_eq!(cmd, "something2"),
+                _ => panic!("Expected Cmd::Cmd"),
+            }
+            match &group[1] {
+                Cmd::TaskName(task_name) => assert_eq!(task_name, "something3"),
+                _ => panic!("Expected Cmd::TaskName"),
+            }
+        }
+        _ => panic!("Expected Cmd::Parallel"),
+    }
+
+    let task = get_task(
+        "sample",
+        r#"
+        parallel: true
+        jobs: 0
+        cmds: ["cmd1", "cmd2"]
+    "#,
+        None,
+    );
+    let expected_error = AwareTaskError::new(
+        "sample",
+        TaskError::ConfigError(String::from("`jobs` must be greater than 0.")),
+    );
+    assert_eq!(task.unwrap_err().to_string(), expected_error.to_string());
+}
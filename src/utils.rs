@@ -1,14 +1,16 @@
 #[cfg(test)]
 #[path = "utils_test.rs"]
+#[allow(clippy::useless_vec)]
 mod utils_test;
 
+use crate::errors::MissingTaskError;
 use crate::tasks::Task;
 use crate::types::DynErrResult;
 use dotenv_parser::parse_dotenv;
 use lazy_static::lazy_static;
 use petgraph::graphmap::DiGraphMap;
 use std::borrow::Cow;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env::current_dir;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
@@ -17,6 +19,9 @@ use std::{env, fs};
 /// To uniquely identify the temporary folder. Constant so that the scripts are cached.
 pub(crate) const TMP_FOLDER_NAMESPACE: &str = "adrianmrit.mom";
 
+/// Maximum number of names suggested when a name is not found
+const MAX_SUGGESTIONS: usize = 3;
+
 #[cfg(test)]
 lazy_static! {
     static ref HOME_DIR: String = {
@@ -95,8 +100,30 @@ pub(crate) fn to_os_task_name(task_name: &str) -> String {
 /// * `tasks`: Hashmap of name to task
 ///
 /// returns: Result<GraphMap<&str, (), Directed>, Box<dyn Error, Global>>
-pub(crate) fn get_task_dependency_graph<'a>(
+pub(crate) fn get_task_dependency_graph(
+    tasks: &HashMap<String, Task>,
+) -> DynErrResult<DiGraphMap<&str, ()>> {
+    build_task_graph(tasks, Task::get_dependencies)
+}
+
+/// Same as `get_task_dependency_graph`, but only contains the relations declared with `deps`,
+/// that is, the tasks that must run before each task.
+///
+/// # Arguments
+///
+/// * `tasks`: Hashmap of name to task
+///
+/// returns: Result<GraphMap<&str, (), Directed>, Box<dyn Error, Global>>
+pub(crate) fn get_task_deps_graph(
+    tasks: &HashMap<String, Task>,
+) -> DynErrResult<DiGraphMap<&str, ()>> {
+    build_task_graph(tasks, Task::get_deps)
+}
+
+/// Builds a graph of the given tasks, where the edges are given by `get_dependencies`.
+fn build_task_graph<'a>(
     tasks: &'a HashMap<String, Task>,
+    get_dependencies: fn(&'a Task) -> Vec<&'a str>,
 ) -> DynErrResult<DiGraphMap<&'a str, ()>> {
     let mut graph: DiGraphMap<&'a str, ()> = DiGraphMap::new();
 
@@ -104,25 +131,93 @@ pub(crate) fn get_task_dependency_graph<'a>(
         // The dependency graph must contain all nodes, even if they do not have any relations.
         // So that we can use the graph to traverse the tasks in the correct order.
         graph.add_node(task_name);
-        for base_name in task.get_dependencies() {
-            let os_base_name = to_os_task_name(base_name);
-            if let Some((key, _)) = tasks.get_key_value(&os_base_name) {
-                graph.add_edge(task_name, key, ());
-            } else if let Some((key, _)) = tasks.get_key_value(base_name) {
-                graph.add_edge(task_name, key, ());
-            } else {
-                return Err(format!(
-                    "Task {} cannot inherit from non-existing task {}.",
-                    task_name, base_name
-                )
-                .into());
-            }
+        for base_name in get_dependencies(task) {
+            match find_task_key(tasks, base_name) {
+                Some(key) => graph.add_edge(task_name, key, ()),
+                None => return Err(MissingTaskError::new(task_name, base_name).into()),
+            };
         }
     }
 
     Ok(graph)
 }
 
+/// Returns the key of the task with the given name, preferring the version for the current OS.
+fn find_task_key<'a>(tasks: &'a HashMap<String, Task>, task_name: &str) -> Option<&'a str> {
+    tasks
+        .get_key_value(&to_os_task_name(task_name))
+        .or_else(|| tasks.get_key_value(task_name))
+        .map(|(key, _)| key.as_str())
+}
+
+/// Returns the errors of all the tasks that refer to tasks that do not exist, sorted by the name
+/// of the task. Building the dependency graph stops at the first one, so this is used to report
+/// all of them.
+///
+/// # Arguments
+///
+/// * `tasks`: Hashmap of name to task
+///
+/// returns: Vec<MissingTaskError>
+pub(crate) fn get_missing_task_errors(tasks: &HashMap<String, Task>) -> Vec<MissingTaskError> {
+    let mut task_names: Vec<&String> = tasks.keys().collect();
+    task_names.sort();
+    let mut errors = Vec::new();
+    for task_name in task_names {
+        for name in tasks[task_name].get_dependencies() {
+            if find_task_key(tasks, name).is_none() {
+                errors.push(MissingTaskError::new(task_name, name));
+            }
+        }
+    }
+    errors
+}
+
+/// Returns a cycle in the given graph as the list of nodes that form it, where the first node is
+/// repeated at the end, e.g. `a -> b -> a`. The cycle starts at its smallest node, so that the
+/// same cycle is always returned in the same way. Returns None if the graph does not have cycles.
+///
+/// # Arguments
+///
+/// * `graph`: Graph to search
+/// * `start`: Node to start searching from, usually the one reported by `toposort`
+pub(crate) fn find_cycle<'a>(
+    graph: &DiGraphMap<&'a str, ()>,
+    start: &'a str,
+) -> Option<Vec<&'a str>> {
+    fn visit<'a>(
+        graph: &DiGraphMap<&'a str, ()>,
+        node: &'a str,
+        path: &mut Vec<&'a str>,
+        visited: &mut HashSet<&'a str>,
+    ) -> Option<Vec<&'a str>> {
+        if let Some(position) = path.iter().position(|n| *n == node) {
+            return Some(path[position..].to_vec());
+        }
+        if !visited.insert(node) {
+            return None;
+        }
+        path.push(node);
+        for next in graph.neighbors(node) {
+            if let Some(cycle) = visit(graph, next, path, visited) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        None
+    }
+
+    let mut visited = HashSet::new();
+    let mut cycle = std::iter::once(start)
+        .chain(graph.nodes())
+        .find_map(|node| visit(graph, node, &mut Vec::new(), &mut visited))?;
+
+    let smallest = (0..cycle.len()).min_by_key(|i| cycle[*i]).unwrap_or(0);
+    cycle.rotate_left(smallest);
+    cycle.push(cycle[0]);
+    Some(cycle)
+}
+
 /// Returns the path relative to the base. If path is already absolute, it will be returned instead.
 ///
 /// # Arguments
@@ -180,6 +275,42 @@ pub(crate) fn read_env_file<S: AsRef<OsStr> + ?Sized>(
     }
 }
 
+/// Reads the variables of a YAML, JSON or TOML file from the given path, choosing the format by
+/// the extension of the file.
+///
+/// # Arguments
+/// * `path`: Path of the vars file
+///
+/// returns: DynErrResult<HashMap<String, serde_yaml::Value>>
+pub(crate) fn read_vars_file<S: AsRef<OsStr> + ?Sized>(
+    path: &S,
+) -> DynErrResult<HashMap<String, serde_yaml::Value>> {
+    let path = Path::new(path);
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            return Err(format!("Failed to read vars file at {}: {}", path.display(), err).into())
+        }
+    };
+
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    let result = match extension.as_deref() {
+        Some("yml") | Some("yaml") => serde_yaml::from_str(&content).map_err(|e| e.to_string()),
+        Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+        _ => Err(String::from(
+            "unsupported format, expected a .yml, .yaml, .json or .toml file",
+        )),
+    };
+
+    match result {
+        Ok(vars) => Ok(vars),
+        Err(err) => Err(format!("Failed to parse vars file at {}: {}", path.display(), err).into()),
+    }
+}
+
 /// Split a command into its arguments. This is a very simple implementation
 /// but it should be enough for most cases.
 pub(crate) fn split_command(val: &str) -> Vec<String> {
@@ -249,6 +380,7 @@ pub(crate) fn join_commands(commands: &[impl AsRef<str>]) -> String {
 /// # Arguments
 /// * `val`: String to expand
 /// * `env`: Environment variables set in the config file
+///
 /// returns: Cow<'a, str>
 pub(crate) fn expand_arg<'a, S: AsRef<str> + ?Sized>(
     // Accept &str and String
@@ -274,6 +406,7 @@ pub(crate) fn expand_arg<'a, S: AsRef<str> + ?Sized>(
 /// # Arguments
 /// * `args`: Arguments to expand
 /// * `env`: Environment variables set in the config file
+///
 /// returns: Vec<Cow<'a, str>>
 pub(crate) fn expand_args<'a>(
     // Accept [&str] and [String]
@@ -282,3 +415,59 @@ pub(crate) fn expand_args<'a>(
 ) -> Vec<Cow<'a, str>> {
     args.iter().map(|arg| expand_arg(arg, env)).collect()
 }
+
+/// Returns the candidates with a name close to the given one, closest first, to suggest them
+/// when the name is not found. The allowed edit distance grows with the length of the name, so
+/// that typos in long names are still caught, and letter case is ignored.
+///
+/// # Arguments
+///
+/// * `name`: Name that was not found
+/// * `candidates`: Names that exist
+///
+/// returns: Vec<String>
+pub(crate) fn get_suggestions<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut suggestions: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| {
+            let distance = strsim::damerau_levenshtein(&name, &candidate.to_lowercase());
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    suggestions.sort();
+    suggestions.dedup();
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| String::from(candidate))
+        .collect()
+}
+
+/// Returns the environment variables that differ from the parent ones, sorted by name, as
+/// `+ NAME=value` for the ones that are added and `~ NAME=value` for the ones that change.
+///
+/// # Arguments
+///
+/// * `env`: Environment variables set for a command
+/// * `parent_env`: Environment variables the command would inherit
+///
+/// returns: Vec<String>
+pub(crate) fn get_env_diff(
+    env: &HashMap<String, String>,
+    parent_env: &HashMap<String, String>,
+) -> Vec<String> {
+    let env: BTreeMap<&String, &String> = env.iter().collect();
+    env.into_iter()
+        .filter_map(|(key, value)| match parent_env.get(key) {
+            None => Some(format!("+ {}={}", key, value)),
+            Some(parent_value) if parent_value != value => Some(format!("~ {}={}", key, value)),
+            Some(_) => None,
+        })
+        .collect()
+}
@@ -52,6 +52,50 @@ fn test_read_env_file() {
     assert_eq!(env_map.get("TEST_VAR"), Some(&"test_value".to_string()));
 }
 
+#[test]
+fn test_read_vars_file() {
+    let tmp_dir = TempDir::new().unwrap();
+    let files = [
+        ("vars.yml", "name: mom\nversions: {python: \"3.11\"}"),
+        (
+            "vars.json",
+            r#"{"name": "mom", "versions": {"python": "3.11"}}"#,
+        ),
+        ("vars.toml", "name = \"mom\"\n[versions]\npython = \"3.11\""),
+    ];
+    for (name, content) in files {
+        let vars_file_path = tmp_dir.join(name);
+        fs::write(&vars_file_path, content).unwrap();
+        let vars = read_vars_file(&vars_file_path).unwrap();
+        assert_eq!(vars.get("name").unwrap(), "mom");
+        assert_eq!(vars.get("versions").unwrap()["python"], "3.11");
+    }
+}
+
+#[test]
+fn test_read_vars_file_invalid() {
+    let tmp_dir = TempDir::new().unwrap();
+    let vars_file_path = tmp_dir.join("vars.ini");
+    fs::write(&vars_file_path, "name = mom").unwrap();
+    let err = read_vars_file(&vars_file_path).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Failed to parse vars file at {}: unsupported format, expected a .yml, .yaml, .json or .toml file",
+            vars_file_path.display()
+        )
+    );
+
+    let vars_file_path = tmp_dir.join("vars.json");
+    fs::write(&vars_file_path, "[1, 2]").unwrap();
+    let err = read_vars_file(&vars_file_path).unwrap_err();
+    let expected_err = format!(
+        "Failed to parse vars file at {}: ",
+        vars_file_path.display()
+    );
+    assert!(err.to_string().contains(&expected_err));
+}
+
 #[test]
 fn test_get_path_relative_to_base() {
     let base = "/home/user";
@@ -187,3 +231,61 @@ fn test_expand_args() {
     let expanded_args = expand_args(&args, &envs);
     assert_eq!(expanded_args, expected);
 }
+
+#[test]
+fn test_find_cycle() {
+    let mut graph: DiGraphMap<&str, ()> = DiGraphMap::new();
+    graph.add_edge("c", "a", ());
+    graph.add_edge("a", "b", ());
+    graph.add_edge("d", "c", ());
+    assert_eq!(find_cycle(&graph, "d"), None);
+
+    graph.add_edge("b", "c", ());
+    assert_eq!(find_cycle(&graph, "d"), Some(vec!["a", "b", "c", "a"]));
+    assert_eq!(find_cycle(&graph, "b"), Some(vec!["a", "b", "c", "a"]));
+
+    let mut graph: DiGraphMap<&str, ()> = DiGraphMap::new();
+    graph.add_edge("a", "a", ());
+    assert_eq!(find_cycle(&graph, "a"), Some(vec!["a", "a"]));
+}
+
+#[test]
+fn test_get_suggestions() {
+    let candidates = [
+        "build",
+        "build.linux",
+        "test",
+        "integration-tests",
+        "frontend:build",
+    ];
+    assert_eq!(get_suggestions("biuld", candidates), vec!["build"]);
+    assert_eq!(get_suggestions("Build", candidates), vec!["build"]);
+    assert_eq!(
+        get_suggestions("integation-test", candidates),
+        vec!["integration-tests"]
+    );
+    assert_eq!(
+        get_suggestions("fronted:build", candidates),
+        vec!["frontend:build"]
+    );
+    assert!(get_suggestions("deploy", candidates).is_empty());
+}
+
+#[test]
+fn test_get_env_diff() {
+    let env = HashMap::from([
+        (String::from("NEW"), String::from("new")),
+        (String::from("CHANGED"), String::from("new")),
+        (String::from("SAME"), String::from("same")),
+    ]);
+    let parent_env = HashMap::from([
+        (String::from("CHANGED"), String::from("old")),
+        (String::from("SAME"), String::from("same")),
+        (String::from("PARENT"), String::from("parent")),
+    ]);
+    assert_eq!(
+        get_env_diff(&env, &parent_env),
+        vec!["~ CHANGED=new", "+ NEW=new"]
+    );
+    assert!(get_env_diff(&HashMap::new(), &parent_env).is_empty());
+}
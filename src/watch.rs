@@ -0,0 +1,153 @@
+#[cfg(test)]
+#[path = "watch_test.rs"]
+mod watch_test;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::args::ArgsContext;
+use crate::fingerprints::{get_fingerprint, FingerprintMethod};
+use crate::mom_files::MomFile;
+use crate::print_utils::MomOutput;
+use crate::run_context::{set_interrupt_handler, RunContext};
+use crate::tasks::Task;
+use crate::types::DynErrResult;
+
+/// How often to check the watched files for changes.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long the watched files must stay unchanged before running the task again, so that
+/// multiple changes in a short period only trigger one run.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Returns the fingerprint of the files watched by the task.
+fn get_watch_fingerprint(task: &Task, mom_file: &MomFile) -> DynErrResult<String> {
+    get_fingerprint(
+        &mom_file.directory,
+        task.get_watch_patterns(),
+        FingerprintMethod::Timestamp,
+    )
+}
+
+/// Detects changes in the watched files, only reporting them once the files stay unchanged for
+/// `WATCH_DEBOUNCE`, so that multiple changes in a short period only trigger one run.
+struct ChangeDetector<F> {
+    /// Computes the fingerprint of the watched files
+    get_fingerprint: F,
+    /// Fingerprint of the files when the last change was reported, if it could be computed
+    fingerprint: Option<String>,
+    /// Latest fingerprint of the files after they changed, and when it last changed
+    pending: Option<(Option<String>, Instant)>,
+}
+
+impl<F: FnMut() -> DynErrResult<String>> ChangeDetector<F> {
+    /// Creates a new ChangeDetector, failing if the fingerprint of the files cannot be computed,
+    /// i.e. because a pattern is not valid.
+    fn new(mut get_fingerprint: F) -> DynErrResult<Self> {
+        let fingerprint = get_fingerprint()?;
+        Ok(ChangeDetector {
+            get_fingerprint,
+            fingerprint: Some(fingerprint),
+            pending: None,
+        })
+    }
+
+    /// Checks the files again, and returns whether they changed and stayed unchanged since for
+    /// `WATCH_DEBOUNCE`. Errors computing the fingerprint count as a change, as they are
+    /// expected while files are being replaced, i.e. when an editor saves them atomically.
+    ///
+    /// # Arguments
+    ///
+    /// * `now`: Time of the check
+    fn poll(&mut self, now: Instant) -> bool {
+        let new_fingerprint = (self.get_fingerprint)().ok();
+        match self.pending.take() {
+            None => {
+                if new_fingerprint != self.fingerprint {
+                    self.pending = Some((new_fingerprint, now));
+                }
+                false
+            }
+            Some((fingerprint, last_change)) => {
+                if fingerprint != new_fingerprint {
+                    self.pending = Some((new_fingerprint, now));
+                    false
+                } else if now.duration_since(last_change) < WATCH_DEBOUNCE {
+                    self.pending = Some((fingerprint, last_change));
+                    false
+                } else {
+                    self.fingerprint = fingerprint;
+                    true
+                }
+            }
+        }
+    }
+}
+
+/// Runs the task, and runs it again every time the files matching its `watch` patterns change,
+/// killing the previous run if it is still running. Returns when the user presses Ctrl-C.
+///
+/// # Arguments
+///
+/// * `task`: Task to run
+/// * `args`: Arguments to run the task with
+/// * `mom_file`: Mom file the task belongs to
+/// * `ctx`: Context with the options to run the task with. Each run uses a renewed copy of it
+pub(crate) fn watch_task(
+    task: &Task,
+    args: &ArgsContext,
+    mom_file: &MomFile,
+    ctx: &RunContext,
+) -> DynErrResult<()> {
+    if task.get_watch_patterns().is_empty() {
+        return Err(format!(
+            "Task `{}` does not have `watch` or `sources` patterns.",
+            task.get_name()
+        )
+        .into());
+    }
+
+    set_interrupt_handler();
+    let mut detector = ChangeDetector::new(|| get_watch_fingerprint(task, mom_file))?;
+
+    loop {
+        // Each run is isolated, so that the whole process tree can be killed on changes
+        let run_ctx = ctx.renew().child();
+        let changed = thread::scope(|scope| {
+            let run = scope.spawn(|| {
+                // Errors are expected if the run was killed on purpose
+                if let Err(e) = task.run(args, mom_file, &run_ctx) {
+                    if !run_ctx.is_cancelled() && !run_ctx.is_interrupted() {
+                        eprintln!("{}", e.to_string().mom_error());
+                    }
+                }
+            });
+
+            let mut waiting_message_shown = false;
+            loop {
+                thread::sleep(WATCH_POLL_INTERVAL);
+                if run_ctx.is_interrupted() {
+                    run.join().unwrap_or(());
+                    return false;
+                }
+                if run.is_finished() && !waiting_message_shown {
+                    println!("{}", "Waiting for changes...".mom_info());
+                    waiting_message_shown = true;
+                }
+                if detector.poll(Instant::now()) {
+                    run_ctx.cancel();
+                    run.join().unwrap_or(());
+                    return true;
+                }
+            }
+        });
+
+        if !changed {
+            return Ok(());
+        }
+        println!(
+            "{}",
+            format!("Changes detected, running {} again", task.get_name()).mom_info()
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use super::*;
+use std::cell::RefCell;
+
+/// Returns a ChangeDetector whose fingerprint is the value of the given cell, or an error if
+/// it is None.
+fn get_detector<'a>(
+    value: &'a RefCell<Option<&'static str>>,
+) -> ChangeDetector<impl FnMut() -> DynErrResult<String> + 'a> {
+    ChangeDetector::new(|| match *value.borrow() {
+        Some(value) => Ok(String::from(value)),
+        None => Err("File not found".into()),
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_change_detector_debounce() {
+    let value = RefCell::new(Some("a"));
+    let mut detector = get_detector(&value);
+    let start = Instant::now();
+
+    assert!(!detector.poll(start));
+    assert!(!detector.poll(start + WATCH_DEBOUNCE * 2));
+
+    // Changes are only reported once the files stay unchanged for WATCH_DEBOUNCE
+    *value.borrow_mut() = Some("b");
+    assert!(!detector.poll(start));
+    *value.borrow_mut() = Some("c");
+    assert!(!detector.poll(start + WATCH_DEBOUNCE / 2));
+    assert!(!detector.poll(start + WATCH_DEBOUNCE));
+    assert!(detector.poll(start + WATCH_DEBOUNCE / 2 + WATCH_DEBOUNCE));
+
+    // Reported only once
+    assert!(!detector.poll(start + WATCH_DEBOUNCE * 3));
+}
+
+#[test]
+fn test_change_detector_errors() {
+    let value = RefCell::new(Some("a"));
+    let mut detector = get_detector(&value);
+    let start = Instant::now();
+
+    // A file being replaced counts as a change, and the detector keeps working
+    *value.borrow_mut() = None;
+    assert!(!detector.poll(start));
+    *value.borrow_mut() = Some("b");
+    assert!(!detector.poll(start));
+    assert!(detector.poll(start + WATCH_DEBOUNCE));
+
+    // Errors that persist are reported once
+    *value.borrow_mut() = None;
+    assert!(!detector.poll(start));
+    assert!(detector.poll(start + WATCH_DEBOUNCE));
+    assert!(!detector.poll(start + WATCH_DEBOUNCE * 2));
+
+    // Invalid patterns are reported when it is created
+    assert!(
+        ChangeDetector::new(|| -> DynErrResult<String> { Err("Invalid pattern".into()) }).is_err()
+    );
+}
@@ -1,6 +1,6 @@
 // At the moment assert_cmd::Command only works in tests outside of the src directory.
 
-use std::{fs::File, io::Write};
+use std::{fs, fs::File, io::Write};
 
 use assert_cmd::Command;
 use assert_fs::TempDir;
@@ -8,6 +8,16 @@ use mom_task::print_utils::MomOutput;
 use mom_task::tasks::DRY_RUN_MESSAGE;
 use predicates::prelude::{predicate, PredicateBooleanExt};
 
+/// Returns the output of a dry run without the plan printed under each command, so that only
+/// the commands it would run are left.
+fn without_dry_run_plan(stdout: &[u8]) -> String {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .filter(|line| !line.starts_with("[mom]   "))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
 #[test]
 fn test_no_mom_file_discovered() {
     let tmp_dir = TempDir::new().unwrap();
@@ -401,20 +411,17 @@ fn test_run_cmds() -> Result<(), Box<dyn std::error::Error>> {
     cmd.arg("testing");
     cmd.arg("hi");
     cmd.arg("--name=world");
-    cmd.assert().success().stdout(predicate::str::contains(
-        format!(
+    let assert = cmd.assert().success();
+    let output = without_dry_run_plan(&assert.get_output().stdout);
+    assert!(output.contains(
+        &format!(
             r#"testing.cmds.0: some command
-{DRY_RUN_MESSAGE}
 testing.cmds.1: some other command
-{DRY_RUN_MESSAGE}
 testing.cmds.2.task_1.cmds.0: some command
-{DRY_RUN_MESSAGE}
 testing.cmds.2.task_1.cmds.1: some other command
-{DRY_RUN_MESSAGE}
 testing.cmds.3.task_3: program hi world
-{DRY_RUN_MESSAGE}
 testing.cmds.4: program hello
-{DRY_RUN_MESSAGE}"#,
+{DRY_RUN_MESSAGE}"#
         )
         .mom_just_prefix(),
     ));
@@ -480,28 +487,20 @@ tasks:
     cmd.current_dir(tmp_dir.path());
     cmd.arg("--dry");
     cmd.arg("test3");
-    cmd.assert().success().stdout(predicate::str::contains(
-        format!(
+    let assert = cmd.assert().success();
+    let output = without_dry_run_plan(&assert.get_output().stdout);
+    assert!(output.contains(
+        &format!(
             r#"test3.cmds.0: echo VAR1: VAL1.3
-{DRY_RUN_MESSAGE}
 test3.cmds.1: echo VAR2: VAL2.2
-{DRY_RUN_MESSAGE}
 test3.cmds.2: echo VAR3: VAL3
-{DRY_RUN_MESSAGE}
 test3.cmds.3.test4.cmds.0: echo VAR1: VAL1.3
-{DRY_RUN_MESSAGE}
 test3.cmds.3.test4.cmds.1: echo VAR2: VAL2.2
-{DRY_RUN_MESSAGE}
 test3.cmds.3.test4.cmds.2: echo VAR3: VAL3.4
-{DRY_RUN_MESSAGE}
 test3.cmds.3.test4.cmds.3: echo VAR4: VAL4
-{DRY_RUN_MESSAGE}
 test3.cmds.4.cmds.0: echo VAR1: VAL1.4
-{DRY_RUN_MESSAGE}
 test3.cmds.4.cmds.1: echo VAR2: VAL2.4
-{DRY_RUN_MESSAGE}
 test3.cmds.4.cmds.2: echo VAR3: VAL3.4
-{DRY_RUN_MESSAGE}
 test3.cmds.4.cmds.3: echo VAR4: VAL4.3
 {DRY_RUN_MESSAGE}
 "#
@@ -603,8 +602,9 @@ tasks:
         .success()
         .stdout(
             predicate::str::contains("[mom] hello: python -m").and(predicate::str::contains(
-                "[mom] Script Begin:\nprint('hello world')\n[mom] Script End.\n[mom] Dry run mode, nothing executed.",
-            )),
+                "[mom] Script Begin:\nprint('hello world')\n[mom] Script End.\n[mom]   argv: [\"python\", \"-m\", ",
+            ))
+            .and(predicate::str::contains(DRY_RUN_MESSAGE.mom_just_prefix())),
         );
     Ok(())
 }
@@ -662,24 +662,18 @@ tasks:
     cmd.current_dir(tmp_dir.path());
     cmd.arg("--dry");
     cmd.arg("test");
-    cmd.assert().success().stdout(predicate::str::contains(
-        format!(
+    let assert = cmd.assert().success();
+    let output = without_dry_run_plan(&assert.get_output().stdout);
+    assert!(output.contains(
+        &format!(
             r#"test.cmds.0: echo var1: val1
-{DRY_RUN_MESSAGE}
 test.cmds.1: echo var2: val2.1
-{DRY_RUN_MESSAGE}
 test.cmds.2: echo var3: 4 5 6
-{DRY_RUN_MESSAGE}
 test.cmds.3: echo user: user1 18
-{DRY_RUN_MESSAGE}
 test.cmds.4.cmds.0: echo var1: val1.1
-{DRY_RUN_MESSAGE}
 test.cmds.4.cmds.1: echo var2: val2.1
-{DRY_RUN_MESSAGE}
 test.cmds.4.cmds.2: echo var3: 4 5 6
-{DRY_RUN_MESSAGE}
 test.cmds.4.cmds.3: echo user: user1 18
-{DRY_RUN_MESSAGE}
 test.cmds.5.other-test.cmds.0: echo var2: val2.1
 {DRY_RUN_MESSAGE}
 "#
@@ -732,10 +726,11 @@ tasks:
     cmd.arg("--dry");
     cmd.arg("test");
     cmd.arg("hello");
-    cmd.assert().success().stdout(predicate::str::contains(
-        format!(
+    let assert = cmd.assert().success();
+    let output = without_dry_run_plan(&assert.get_output().stdout);
+    assert!(output.contains(
+        &format!(
             r#"test.cmds.0: echo hello world
-{DRY_RUN_MESSAGE}
 test.cmds.1: echo HELLO and bye
 {DRY_RUN_MESSAGE}
 "#
@@ -778,8 +773,10 @@ tasks:
     cmd.arg("--dry");
     cmd.arg("run");
     cmd.arg("t2");
-    cmd.assert().success().stdout(predicate::str::contains(
-        format!(
+    let assert = cmd.assert().success();
+    let output = without_dry_run_plan(&assert.get_output().stdout);
+    assert!(output.contains(
+        &format!(
             r#"run.cmds.0.task1 skipped
 run.cmds.1.task2.cmds.0: echo "task2 executed"
 {DRY_RUN_MESSAGE}
@@ -812,11 +809,1803 @@ tasks:
     let mut cmd = Command::cargo_bin("mom").unwrap();
     cmd.current_dir(tmp_dir.path());
     cmd.arg("test");
+    cmd.assert().success().stdout(predicate::str::contains(
+        r#"test.cmds.0: echo "hello world"
+hello world
+"#,
+    ));
+}
+
+#[test]
+fn test_parallel_group() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    test:
+        cmds:
+            - parallel:
+                - echo "hello"
+                - echo "world"
+            - echo "bye"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+
+    let hello = output.find("test.cmds.0.0: echo hello").unwrap();
+    let world = output.find("test.cmds.0.1: echo world").unwrap();
+    let bye = output.find("test.cmds.1: echo bye").unwrap();
+    assert!(hello < bye);
+    assert!(world < bye);
+}
+
+#[test]
+fn test_parallel_fail_fast() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    slow:
+        script: sleep 10
+        windows:
+            script: timeout /t 10
+
+    fail:
+        script: exit 3
+
+    test:
+        parallel: true
+        cmds:
+            - task: slow
+            - task: fail
+
+    test_wait_all:
+        parallel: true
+        fail_fast: false
+        jobs: 1
+        cmds:
+            - task: fail
+            - echo "still running"
+            - task: fail
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let start = std::time::Instant::now();
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Task `test.cmds.1.fail` failed"));
+    // The slow task should have been killed
+    assert!(start.elapsed() < std::time::Duration::from_secs(8));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test_wait_all");
+    // The exit code of the failed commands is kept
+    cmd.assert()
+        .code(3)
+        .stdout(predicate::str::contains("still running"))
+        .stderr(predicate::str::contains("2 commands failed"));
+}
+
+#[test]
+fn test_deps_run_once() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    a:
+        cmds:
+            - echo "a"
+
+    b:
+        deps: a
+        cmds:
+            - echo "b"
+
+    c:
+        deps: [a, b]
+        cmds:
+            - echo "c"
+            - task: b
+
+    d:
+        deps: [b, c]
+        parallel: true
+        cmds:
+            - echo "d"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("c");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert_eq!(output.matches("] a.cmds.0: echo a").count(), 1);
+    let a = output.find("] a.cmds.0: echo a").unwrap();
+    let b = output.find("] b.cmds.0: echo b").unwrap();
+    let c = output.find("] c.cmds.0: echo c").unwrap();
+    assert!(a < b);
+    assert!(b < c);
+    // Running the task from cmds is not a dependency, so it runs again
+    assert!(output.contains("c.cmds.1.b.cmds.0: echo b"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("d");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert_eq!(output.matches("] a.cmds.0: echo a").count(), 1);
+    assert_eq!(output.matches("] b.cmds.0: echo b").count(), 1);
+    let a = output.find("] a.cmds.0: echo a").unwrap();
+    let b = output.find("] b.cmds.0: echo b").unwrap();
+    let c = output.find("] c.cmds.0: echo c").unwrap();
+    let d = output.find("] d.cmds.0: echo d").unwrap();
+    assert!(a < b);
+    assert!(b < c);
+    assert!(c < d);
+}
+
+#[test]
+fn test_sources_up_to_date() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    build:
+        sources: "src/*.txt"
+        generates: ["out.txt"]
+        cmds:
+            - echo "building"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+    std::fs::create_dir(tmp_dir.join("src")).unwrap();
+    let mut source = File::create(tmp_dir.join("src").join("a.txt")).unwrap();
+    source.write_all(b"hello").unwrap();
+    File::create(tmp_dir.join("out.txt")).unwrap();
+
+    let run = || {
+        let mut cmd = Command::cargo_bin("mom").unwrap();
+        cmd.current_dir(tmp_dir.path());
+        cmd.arg("build");
+        cmd.assert().success()
+    };
+
+    run().stdout(predicate::str::contains("building"));
+    run().stdout(
+        predicate::str::contains("build up to date")
+            .and(predicate::str::contains("building").not()),
+    );
+
+    // Modifying a source runs the task again
+    source.write_all(b" world").unwrap();
+    run().stdout(predicate::str::contains("building"));
+    run().stdout(predicate::str::contains("build up to date"));
+
+    // Removing a generated file runs the task again
+    std::fs::remove_file(tmp_dir.join("out.txt")).unwrap();
+    run().stdout(predicate::str::contains("building"));
+}
+
+#[test]
+fn test_sources_with_changed_definition() {
+    let tmp_dir = TempDir::new().unwrap();
+    let mom_file_path = tmp_dir.join("mom.root.yml");
+    let write_mom_file = |message: &str| {
+        let mut file = File::create(&mom_file_path).unwrap();
+        file.write_all(
+            format!(
+                r#"
+version: 1
+
+tasks:
+    build:
+        sources: "*.txt"
+        cmds:
+            - echo "{message} {{{{ args.0 }}}}"
+    first:
+        cmds:
+            - task: build
+    second:
+        cmds:
+            - task: build
+"#
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+    };
+    write_mom_file("building");
+    File::create(tmp_dir.join("a.txt")).unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("mom").unwrap();
+        cmd.current_dir(tmp_dir.path());
+        cmd.args(args);
+        cmd.assert().success()
+    };
+
+    run(&["build", "a"]).stdout(predicate::str::contains("building a"));
+    run(&["build", "a"]).stdout(predicate::str::contains("build up to date"));
+
+    // Different arguments run the task again
+    run(&["build", "b"]).stdout(predicate::str::contains("building b"));
+
+    // As well as a change in the definition of the task
+    write_mom_file("compiling");
+    run(&["build", "b"]).stdout(predicate::str::contains("compiling b"));
+    run(&["build", "b"]).stdout(predicate::str::contains("build up to date"));
+
+    // The task is the same when run from the commands of another one
+    run(&["first", "b"]).stdout(predicate::str::contains("first.cmds.0.build up to date"));
+    run(&["second", "b"]).stdout(predicate::str::contains("second.cmds.0.build up to date"));
+}
+
+#[test]
+fn test_watch() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    test:
+        watch: "*.txt"
+        cmds:
+            - echo "running"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+    File::create(tmp_dir.join("a.txt")).unwrap();
+    let output_path = tmp_dir.join("output.log");
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("mom"))
+        .current_dir(tmp_dir.path())
+        .args(["--watch", "test"])
+        .stdout(File::create(&output_path).unwrap())
+        .spawn()
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    let mut source = File::create(tmp_dir.join("b.txt")).unwrap();
+    source.write_all(b"hello").unwrap();
+    std::thread::sleep(std::time::Duration::from_secs(2));
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    let output = std::fs::read_to_string(&output_path).unwrap();
+    assert_eq!(output.matches("test.cmds.0: echo running").count(), 2);
+    assert!(output.contains("Changes detected, running test again"));
+}
+
+#[test]
+fn test_watch_without_patterns() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    test:
+        cmds:
+            - echo "running"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--watch", "test"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Task `test` does not have `watch` or `sources` patterns.",
+    ));
+}
+
+#[test]
+fn test_set_overrides() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+vars:
+    greeting: hello
+
+tasks:
+    child:
+        vars:
+            name: child
+        env:
+            LEVEL: child
+        cmds:
+            - echo "{{ vars.greeting }} {{ vars.name }} $LEVEL"
+
+    test:
+        vars:
+            name: parent
+            items: [a]
+        cmds:
+            - echo "{% for item in vars.items %}{{ item }}{% endfor %} {{ vars.name }} $LEVEL"
+            - task: child
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args([
+        "--set",
+        "vars.name=cli",
+        "--set",
+        "vars.items=[x, y]",
+        "--set",
+        "env.LEVEL=cli",
+        "test",
+    ]);
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains(format!(
-            r#"test.cmds.0: echo "hello world"
-hello world
+        .stdout(predicate::str::contains("\nxy cli cli\n"))
+        .stdout(predicate::str::contains("\nhello cli cli\n"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--set", "name=cli", "test"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Invalid override `name`, the key must start with `vars.` or `env.`.",
+    ));
+}
+
+#[test]
+fn test_params() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    greet:
+        help: Greets someone
+        params:
+            - name: who
+              positional: true
+              required: true
+              help: Who to greet
+            - name: times
+              type: int
+              default: 1
+            - name: loud
+              type: bool
+        cmds:
+            - echo "{% for i in range(end=params.times) %}{% if params.loud %}HELLO{% else %}hello{% endif %} {{ params.who }} {% endfor %}"
 "#
-        )));
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["greet", "--loud", "world", "--times", "2"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\nHELLO world HELLO world \n"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["greet", "world", "--times", "two"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Invalid value `two` for parameter `times`, expected an integer.",
+    ));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["greet"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Missing required parameter `who`.",
+    ));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["-i", "greet"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Params:"))
+        .stdout(predicate::str::contains("--times <int>"))
+        .stdout(predicate::str::contains("(default: 1)"));
+}
+
+#[test]
+fn test_finally() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    cleanup:
+        cmds:
+            - echo "cleanup task"
+
+    test:
+        cmds:
+            - echo "start"
+            - mom_test_unknown_program
+            - echo "never"
+        finally:
+            - echo "cleanup"
+            - task: cleanup
+
+    finally_fails:
+        cmds:
+            - echo "start"
+        finally:
+            - mom_test_unknown_program
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test");
+    let assert = cmd.assert().failure();
+    let output = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(!output.contains("never"));
+    let start = output.find("test.cmds.0: echo start").unwrap();
+    let cleanup = output.find("test.finally.0: echo cleanup").unwrap();
+    let cleanup_task = output.find("test.finally.1.cleanup.cmds.0").unwrap();
+    assert!(start < cleanup);
+    assert!(cleanup < cleanup_task);
+    // The original error is still reported
+    assert.stderr(predicate::str::contains("Task `test` failed"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("finally_fails");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("finally_fails.finally.0"))
+        .stderr(predicate::str::contains("Task `finally_fails` failed"));
+}
+
+#[test]
+fn test_ignore_error() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    test:
+        cmds:
+            - cmd: mom_test_unknown_program
+              ignore_error: true
+            - echo "after ignored"
+            - cmd: python -c "exit(1)"
+              ok_exit_codes: [0, 1]
+            - echo "after exit code"
+
+    test_task:
+        ignore_error: true
+        cmds:
+            - mom_test_unknown_program
+            - echo "after task ignored"
+
+    test_not_ignored:
+        ignore_error: true
+        cmds:
+            - cmd: mom_test_unknown_program
+              ignore_error: false
+            - echo "never"
+
+    test_exit_code:
+        cmds:
+            - cmd: python -c "exit(2)"
+              ok_exit_codes: [0, 1]
+            - echo "never"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("after ignored"))
+        .stdout(predicate::str::contains("after exit code"))
+        .stderr(predicate::str::contains(
+            "test.cmds.0 failed, ignoring the error:",
+        ));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test_task");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("after task ignored"))
+        .stderr(predicate::str::contains(
+            "test_task.cmds.0 failed, ignoring the error:",
+        ));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test_not_ignored");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("never").not());
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test_exit_code");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("never").not())
+        .stderr(predicate::str::contains(
+            "Process terminated with exit code 2",
+        ));
+}
+
+#[test]
+fn test_retries() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    // Fails until it runs for the third time
+    let mut file = File::create(tmp_dir.join("flaky.py")).unwrap();
+    file.write_all(
+        r#"
+import os, sys
+count = int(open("count").read()) if os.path.exists("count") else 0
+open("count", "w").write(str(count + 1))
+sys.exit(0 if count >= 2 else 1)
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    test:
+        cmds:
+            - cmd: python flaky.py
+              retries: 2
+              retry_delay: 10ms
+              retry_backoff: 2
+            - echo "after flaky"
+
+    test_not_enough:
+        retries: 1
+        retry_delay: 10ms
+        cmds:
+            - python flaky.py
+            - echo "never"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("after flaky"))
+        .stderr(predicate::str::contains(
+            "test.cmds.0 failed, retrying in 10ms (1/2):",
+        ))
+        .stderr(predicate::str::contains(
+            "test.cmds.0 failed, retrying in 20ms (2/2):",
+        ));
+
+    fs::remove_file(tmp_dir.join("count")).unwrap();
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test_not_enough");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("never").not())
+        .stderr(predicate::str::contains(
+            "test_not_enough.cmds.0 failed, retrying in 10ms (1/1):",
+        ));
+}
+
+#[test]
+fn test_timeout() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    test_task:
+        timeout: 500ms
+        cmds:
+            - echo "start"
+            - python -c "import time; time.sleep(30)"
+            - echo "never"
+        finally:
+            - echo "cleanup"
+
+    test_cmd:
+        cmds:
+            - cmd: python -c "import time; time.sleep(30)"
+              timeout: 200ms
+              retries: 1
+              retry_delay: 10ms
+            - echo "never"
+
+    test_ignored:
+        cmds:
+            - cmd: python -c "import time; time.sleep(30)"
+              timeout: 200ms
+              ignore_error: true
+            - echo "after timeout"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let start = std::time::Instant::now();
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test_task");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("never").not())
+        .stdout(predicate::str::contains("cleanup"))
+        .stderr(predicate::str::contains(
+            "`test_task` did not finish within 500ms.",
+        ));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test_cmd");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("never").not())
+        .stderr(predicate::str::contains(
+            "test_cmd.cmds.0 failed, retrying in 10ms (1/1):",
+        ))
+        .stderr(predicate::str::contains(
+            "`test_cmd.cmds.0` did not finish within 200ms.",
+        ));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test_ignored");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("after timeout"));
+
+    // The commands are terminated instead of running to completion
+    assert!(start.elapsed() < std::time::Duration::from_secs(20));
+}
+
+#[test]
+fn test_exit_code() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    test:
+        cmds:
+            - python -c "exit(3)"
+
+    test_subtask:
+        cmds:
+            - task: test
+
+    test_dep:
+        deps: test
+        cmds:
+            - echo "never"
+
+    test_config_error:
+        cmds:
+            - echo {{ undefined_var }}
+
+    test_signal:
+        cmds:
+            - python -c "import os, signal; os.kill(os.getpid(), signal.SIGTERM)"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    for task in ["test", "test_subtask", "test_dep"] {
+        let mut cmd = Command::cargo_bin("mom").unwrap();
+        cmd.current_dir(tmp_dir.path());
+        cmd.arg(task);
+        cmd.assert().code(3).stderr(predicate::str::contains(
+            "Process terminated with exit code 3",
+        ));
+    }
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test_config_error");
+    cmd.assert().code(1);
+
+    if cfg!(unix) {
+        let mut cmd = Command::cargo_bin("mom").unwrap();
+        cmd.current_dir(tmp_dir.path());
+        cmd.arg("test_signal");
+        cmd.assert()
+            .code(143)
+            .stderr(predicate::str::contains("Process terminated by signal 15"));
+    }
+}
+
+#[test]
+fn test_register() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    test:
+        vars:
+            name: "mom"
+        cmds:
+            - cmd: python -c "print('  abc123  ')"
+              register: sha
+            - cmd: echo "hello"
+              register: greeting
+            - echo "sha={{ vars.sha }}"
+            - echo "{{ vars.greeting }} {{ vars.name }}"
+        finally:
+            - echo "finally sha={{ vars.sha }}"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test");
+    let assert = cmd.assert().success();
+    let output = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(output.contains("\nsha=abc123\n"));
+    assert!(output.contains("\nhello mom\n"));
+    assert!(output.contains("\nfinally sha=abc123\n"));
+    // The registered output is not printed
+    assert!(!output.contains("\n  abc123  \n"));
+    assert!(!output.contains("\nhello\n"));
+}
+
+#[test]
+fn test_dynamic_values() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::create_dir(tmp_dir.join("sub")).unwrap();
+    File::create(tmp_dir.join("sub").join("marker.txt")).unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+env:
+    SUB_DIR: {sh: "python -c \"print('sub')\""}
+
+    HERE: {sh: "python -c \"import os; print(os.path.basename(os.getcwd()))\""}
+
+vars:
+    enabled: {sh: "python -c \"print('yes')\""}
+    unused: {sh: "python -c \"import sys; sys.exit(3)\""}
+    created: {sh: "python -c \"open('created.txt', 'w'); print('created')\""}
+
+tasks:
+    test:
+        wd: "$SUB_DIR"
+        condition: "{{ vars.enabled == 'yes' }}"
+        cmds:
+            - python -c "import os; print('files=' + ','.join(os.listdir('.')))"
+            - echo "sub=$SUB_DIR here=$HERE"
+
+    dry:
+        cmds:
+            - echo "{{ vars.created }}"
+
+    skipped:
+        vars:
+            enabled: {sh: "python -c \"print('no')\""}
+        condition: "{{ vars.enabled == 'yes' }}"
+        cmds:
+            - echo "should not run"
+
+    broken:
+        env:
+            BROKEN: {sh: "python -c \"import sys; sys.exit(3)\""}
+        cmds:
+            - echo "should not run"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test");
+    let assert = cmd.assert().success();
+    let output = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(output.contains("\nfiles=marker.txt\n"));
+    // The env values the working directory does not refer to run in it
+    assert!(output.contains("\nsub=sub here=sub\n"));
+
+    // Nothing runs in dry mode
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--dry").arg("dry");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "<sh: python -c open('created.txt',",
+        ))
+        .stdout(predicate::str::contains(
+            "+ SUB_DIR=<sh: python -c \"print('sub')\">",
+        ));
+    assert!(!tmp_dir.join("created.txt").exists());
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("skipped");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("skipped skipped"))
+        .stdout(predicate::str::contains("should not run").not());
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("broken");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Could not compute `env.BROKEN`"))
+        .stdout(predicate::str::contains("should not run").not());
+}
+
+#[test]
+fn test_vars_file() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::write(
+        tmp_dir.join("versions.yml"),
+        "python: \"3.11\"\nnode: \"18\"\n",
+    )
+    .unwrap();
+    fs::write(
+        tmp_dir.join("versions.json"),
+        r#"{"node": "20", "rust": "1.75"}"#,
+    )
+    .unwrap();
+    fs::write(tmp_dir.join("tools.toml"), "[tools]\nlinter = \"ruff\"\n").unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+vars_file: ["versions.yml", "versions.json"]
+
+vars:
+    rust: "1.80"
+
+tasks:
+    test:
+        vars_file: tools.toml
+        cmds:
+            - echo "python={{ vars.python }} node={{ vars.node }} rust={{ vars.rust }}"
+            - echo "linter={{ vars.tools.linter }}"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("test");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("python=3.11 node=18 rust=1.80"))
+        .stdout(predicate::str::contains("linter=ruff"));
+}
+
+#[test]
+fn test_vars_file_and_overrides_are_not_dynamic() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::write(
+        tmp_dir.join("data.json"),
+        r#"{"loaded": {"sh": "python -c \"open('loaded.txt', 'w')\""}}"#,
+    )
+    .unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+vars_file: data.json
+
+tasks:
+    test:
+        cmds:
+            - echo "loaded={{ vars.loaded.sh }}"
+            - echo "given={{ vars.given.sh }}"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--set")
+        .arg("vars.given={sh: \"python -c \\\"open('given.txt', 'w')\\\"\"}")
+        .arg("test");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("loaded=python -c"))
+        .stdout(predicate::str::contains("given=python -c"));
+    assert!(!tmp_dir.join("loaded.txt").exists());
+    assert!(!tmp_dir.join("given.txt").exists());
+}
+
+#[test]
+fn test_include() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::create_dir(tmp_dir.join("backend")).unwrap();
+    fs::write(
+        tmp_dir.join("backend").join(".env"),
+        "BACKEND_VAR=backend_env\n",
+    )
+    .unwrap();
+
+    fs::write(
+        tmp_dir.join("backend").join("mom.yml"),
+        r#"
+version: 1
+
+dotenv: .env
+
+tasks:
+    test:
+        wd: "."
+        cmds:
+            - python -c "import os; print('cwd=' + os.path.basename(os.getcwd()))"
+            - echo "var=$BACKEND_VAR"
+
+    build:
+        cmds:
+            - echo "backend built with $BACKEND_VAR"
+"#,
+    )
+    .unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+include:
+    backend: backend/mom.yml
+
+tasks:
+    all:
+        cmds:
+            - task: backend:test
+            - echo "all done"
+
+    prepare:
+        cmds:
+            - echo "prepared"
+
+    deploy:
+        deps: [backend:build, prepare]
+        cmds:
+            - echo "deployed"
+
+    deploy_parallel:
+        deps: [prepare, backend:build]
+        parallel: true
+        cmds:
+            - echo "deployed"
+
+    missing:
+        deps: [backend:missing]
+        cmds:
+            - echo "deployed"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("backend:test");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("cwd=backend"))
+        .stdout(predicate::str::contains("var=backend_env"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("all");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("cwd=backend"))
+        .stdout(predicate::str::contains("var=backend_env"))
+        .stdout(predicate::str::contains("all done"));
+
+    // Tasks of included files can be given in deps with the namespace
+    for task in ["deploy", "deploy_parallel"] {
+        let mut cmd = Command::cargo_bin("mom").unwrap();
+        cmd.current_dir(tmp_dir.path());
+        cmd.arg(task);
+        let assert = cmd.assert().success();
+        let output = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let built = output.find("\nbackend built with backend_env\n").unwrap();
+        let prepared = output.find("\nprepared\n").unwrap();
+        let deployed = output.find("\ndeployed\n").unwrap();
+        assert!(built < deployed);
+        assert!(prepared < deployed);
+    }
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("missing");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("backend:missing"))
+        .stdout(predicate::str::contains("deployed").not());
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("-t");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(" - backend:test"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("frontend:test");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Task frontend:test not found"));
+}
+
+#[test]
+fn test_run_multiple_tasks() {
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    setup:
+        cmds:
+            - echo "setup ran"
+
+    lint:
+        deps: [setup]
+        cmds:
+            - echo "lint ran"
+
+    fail:
+        cmds:
+            - python -c "import sys; sys.exit(4)"
+
+    test:
+        deps: [setup]
+        cmds:
+            - echo "test ran"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--tasks", "lint,test"]);
+    let assert = cmd.assert().success();
+    let output = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(output.contains("lint ran"));
+    assert!(output.contains("test ran"));
+    // Shared dependencies only run once
+    assert_eq!(output.matches("\nsetup ran\n").count(), 1);
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--tasks", "lint,fail", "--tasks", "test"]);
+    cmd.assert()
+        .code(4)
+        .stdout(predicate::str::contains("lint ran"))
+        .stdout(predicate::str::contains("test ran").not());
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--tasks", "fail,unknown,test", "--keep-going"]);
+    cmd.assert()
+        .code(4)
+        .stdout(predicate::str::contains("test ran"))
+        .stderr(predicate::str::contains("Task unknown not found"))
+        .stderr(predicate::str::contains("2 tasks failed: fail, unknown"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--tasks", "lint", "test"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Tasks cannot be given with --tasks and as a command at the same time.",
+    ));
+}
+
+#[test]
+fn test_init() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::write(tmp_dir.join("Cargo.toml"), "[package]\nname = \"sample\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--init", "build"]);
+    cmd.assert().code(1).stderr(predicate::str::contains(
+        "A task cannot be given with --init.",
+    ));
+    assert!(!tmp_dir.join("mom.root.yml").exists());
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--init");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Created"))
+        .stdout(predicate::str::contains("mom.root.yml"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("-t");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("build"))
+        .stdout(predicate::str::contains("test"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--init");
+    cmd.assert().code(1).stderr(predicate::str::contains(
+        "already exists, not overwriting it.",
+    ));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--init", "-f", "tasks/mom.yml"]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["-f", "tasks/mom.yml", "hello"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Hello from mom!"));
+}
+
+#[test]
+fn test_validate() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::create_dir(tmp_dir.join("backend")).unwrap();
+    fs::write(
+        tmp_dir.join("backend").join("mom.yml"),
+        r#"
+version: 1
+
+tasks:
+    test:
+        cmds:
+            - echo {{ args.0
+"#,
+    )
+    .unwrap();
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        r#"
+version: 1
+
+include:
+    backend: ./backend/mom.yml
+
+tasks:
+    build:
+        cmds:
+            - echo building
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--validate");
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains("backend"))
+        .stderr(predicate::str::contains(
+            "Task `test`: Invalid template in `cmds.0`",
+        ))
+        .stderr(predicate::str::contains("Found 1 problem in 1 mom file."));
+
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        r#"
+version: 1
+
+dotenv: missing.env
+
+tasks:
+    build:
+        script: echo building
+        cmds:
+            - echo building
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--validate");
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains("Failed to read env file at"))
+        .stderr(predicate::str::contains(
+            "Task `build`: Cannot set both `cmds` and `script`.",
+        ))
+        .stderr(predicate::str::contains("Found 2 problems in 1 mom file."));
+
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        r#"
+version: 1
+
+tasks:
+    build:
+        cmds:
+            - echo {{ args.0 }}
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--validate");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No problems found in 1 mom file."));
+    // Every task that is not found is reported, not only the first one
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        r#"
+version: 1
+
+tasks:
+    build:
+        deps: [missing_dep]
+        cmds:
+            - task: missing_task
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--validate");
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Task build cannot inherit from non-existing task missing_dep.",
+        ))
+        .stderr(predicate::str::contains(
+            "Task build cannot inherit from non-existing task missing_task.",
+        ))
+        .stderr(predicate::str::contains("Found 2 problems in 1 mom file."));
+
+    // The error the file failed to load with is reported if checking does not find it
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        r#"
+version: 1
+
+tasks:
+    build:
+        deps: [test]
+        cmds:
+            - echo {{ args.0
+    test:
+        deps: [build]
+        cmds:
+            - echo testing
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--validate");
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Found a cyclic dependency for task",
+        ))
+        .stderr(predicate::str::contains(
+            "Task `build`: Invalid template in `cmds.0`",
+        ))
+        .stderr(predicate::str::contains("Found 2 problems in 1 mom file."));
+}
+
+#[test]
+fn test_errors_point_to_the_mom_file() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        r#"
+version: 1
+
+tasks:
+    build:
+        cmds:
+            - echo building
+            - parallel:
+                - echo {{ vars.missing }}
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("build");
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Variable `vars.missing` not found in context",
+        ))
+        .stderr(predicate::str::contains("mom.root.yml:9:19"))
+        .stderr(predicate::str::contains(
+            "9 |                 - echo {{ vars.missing }}",
+        ));
+
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        "version: 1\n\ntasks:\n    build: echo building\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("build");
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "tasks.build: invalid type: string \"echo building\", expected struct Task",
+        ))
+        .stderr(predicate::str::contains("mom.root.yml:4:12"))
+        .stderr(predicate::str::contains("4 |     build: echo building"));
+}
+
+#[test]
+fn test_task_not_found_suggestions() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::create_dir(tmp_dir.join("backend")).unwrap();
+    fs::write(
+        tmp_dir.join("backend").join("mom.yml"),
+        r#"
+version: 1
+
+tasks:
+    integration-tests:
+        cmds:
+            - echo testing
+"#,
+    )
+    .unwrap();
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        r#"
+version: 1
+
+include:
+    backend: backend/mom.yml
+
+tasks:
+    build:
+        cmds:
+            - echo building
+    build-docs:
+        cmds:
+            - echo building docs
+"#,
+    )
+    .unwrap();
+    let root_path = tmp_dir.join("mom.root.yml").to_string_lossy().to_string();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("biuld");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Task biuld not found."))
+        .stderr(predicate::str::contains("Did you mean:"))
+        .stderr(predicate::str::contains(" - build\n"))
+        .stderr(predicate::str::contains("Searched in:"))
+        .stderr(predicate::str::contains(format!(" - {}", root_path)));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--task-info", "backend:integation-test"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(" - backend:integration-tests"));
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("deploy");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Task deploy not found."))
+        .stderr(predicate::str::contains("Did you mean").not());
+}
+
+#[test]
+fn test_explain() {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::write(tmp_dir.join(".env"), "FROM_DOTENV=dotenv\n").unwrap();
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        r#"
+version: 1
+
+dotenv: .env
+
+vars:
+    target: debug
+
+tasks:
+    base:
+        env:
+            FROM_BASE: base
+
+    test:
+        cmds:
+            - echo testing
+
+    build:
+        extend: base
+        cmds:
+            - echo building {{ vars.target }} > built.txt
+            - task: test
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--explain", "build", "--set", "vars.target=release"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "FROM_BASE=base  (base task `base`)",
+        ))
+        .stdout(predicate::str::contains(format!(
+            "FROM_DOTENV=dotenv  (file {}, dotenv {})",
+            tmp_dir.join("mom.root.yml").display(),
+            tmp_dir.join(".env").display()
+        )))
+        .stdout(predicate::str::contains("target=\"release\"  (--set)"))
+        .stdout(predicate::str::contains(
+            "cmds.0: echo building release > built.txt",
+        ))
+        .stdout(predicate::str::contains("cmds.1: task build.cmds.1.test"))
+        .stdout(predicate::str::contains("cmds.0: echo testing"));
+    // Nothing runs
+    assert!(!tmp_dir.join("built.txt").exists());
+
+    let mut cmd = Command::cargo_bin("mom").unwrap();
+    cmd.current_dir(tmp_dir.path());
+    cmd.args(["--explain", "biuld"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Task biuld not found."))
+        .stderr(predicate::str::contains(" - build"));
+}
+
+#[test]
+fn test_dry_run_plan() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = TempDir::new().unwrap();
+    fs::write(
+        tmp_dir.join("mom.root.yml"),
+        r#"
+version: 1
+
+env:
+    MOM_DRY_GREETING: hello
+
+tasks:
+    build:
+        wd: out
+        cmds:
+            - echo {{ env.MOM_DRY_GREETING }} $MOM_DRY_GREETING
+            - cmd: some-program --target debug
+              register: output
+            - some-program {{ vars.output }}last
+            - task:
+                program: other-program
+                args: $MOM_DRY_GREETING
+            - task:
+                script_runner: bash {{ script_path }}
+                script: echo $MOM_DRY_GREETING
+"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin("mom")?;
+    cmd.current_dir(tmp_dir.path());
+    cmd.arg("--dry");
+    cmd.arg("build");
+    let assert = cmd.assert().success();
+    let output = String::from_utf8(assert.get_output().stdout.clone())?;
+    let wd = tmp_dir.path().canonicalize()?;
+    let env = "[mom]   env:\n[mom]     + MOM_DRY_GREETING=hello";
+    assert!(output.contains(&format!(
+        "[mom] build.cmds.0: echo hello hello\n\
+         [mom]   argv: [\"echo\", \"hello\", \"hello\"] (builtin)\n\
+         [mom]   wd: {}\n\
+         {env}\n\
+         [mom] build.cmds.1: some-program --target debug\n\
+         [mom]   argv: [\"some-program\", \"--target\", \"debug\"]\n\
+         [mom]   wd: {}\n\
+         {env}\n\
+         [mom] build.cmds.2: some-program last\n\
+         [mom]   argv: [\"some-program\", \"last\"]\n\
+         [mom]   wd: {}\n\
+         {env}\n\
+         [mom] build.cmds.3: other-program $MOM_DRY_GREETING\n\
+         [mom]   argv: [\"other-program\", \"hello\"]\n\
+         [mom]   wd: {}\n\
+         {env}\n",
+        wd.join("out").display(),
+        wd.join("out").display(),
+        wd.join("out").display(),
+        wd.display(),
+    )));
+    assert!(output.contains("[mom] Script End.\n[mom]   argv: [\"bash\", "));
+    assert!(output.ends_with(&format!("{env}\n{}\n", DRY_RUN_MESSAGE.mom_just_prefix())));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_bash_completions_skip_option_values() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("mom")?;
+    cmd.arg("--completions");
+    cmd.arg("bash");
+    let script = String::from_utf8(cmd.assert().success().get_output().stdout.clone())?;
+
+    // Completes the current word after the others given after `mom`, with the task names given
+    // by a stub of mom
+    let complete = |words: &str, current: &str| -> Result<String, Box<dyn std::error::Error>> {
+        let output = std::process::Command::new("bash")
+            .arg("-c")
+            .arg(format!(
+                r#"{script}
+mom() {{ echo build; echo test; }}
+COMP_WORDS=(mom {words} "{current}")
+COMP_CWORD=$((${{#COMP_WORDS[@]}} - 1))
+_mom
+echo "${{COMPREPLY[@]}}""#
+            ))
+            .output()?;
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    };
+
+    assert_eq!(complete("--format", "")?, "text json yaml");
+    // The value of the option is not taken as the task
+    assert_eq!(complete("--format json", "")?, "build test");
+    assert_eq!(complete("--set", "")?, "");
+    assert_eq!(complete("--set env.X=1", "")?, "build test");
+    assert_eq!(
+        complete("--set env.X=1 --format json --dry", "")?,
+        "build test"
+    );
+    // Only the task after the last comma is completed
+    assert_eq!(complete("--tasks", "lint,b")?, "lint,build");
+    assert_eq!(
+        complete("--tasks", "lint,test,")?,
+        "lint,test,build lint,test,test"
+    );
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_timeout_reads_from_terminal() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = TempDir::new()?;
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml"))?;
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    ask:
+        cmds:
+            - cmd: head -n 1
+              timeout: 5s
+            - echo "done"
+"#
+        .as_bytes(),
+    )?;
+
+    // Runs mom in a pseudo terminal, so that the command with a timeout would be stopped by
+    // SIGTTIN if it was not in the foreground process group, like mom is.
+    let mom = assert_cmd::cargo::cargo_bin("mom");
+    let mut child = std::process::Command::new("script")
+        .current_dir(tmp_dir.path())
+        .arg("-qec")
+        .arg(format!("{} ask", mom.display()))
+        .arg("/dev/null")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(b"answer\n")?;
+    let output = child.wait_with_output()?;
+    let output = String::from_utf8(output.stdout)?;
+
+    assert!(output.contains("ask.cmds.0: head -n 1"));
+    assert!(!output.contains("did not finish within 5s"));
+    assert!(output.contains("ask.cmds.1: echo done"));
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_watch_reads_empty_input() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = TempDir::new()?;
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml"))?;
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    ask:
+        watch: "*.txt"
+        cmds:
+            - head -n 1
+            - echo "done"
+"#
+        .as_bytes(),
+    )?;
+    let output_path = tmp_dir.join("output.log");
+
+    // Runs mom in a pseudo terminal, where the command would be stopped by SIGTTIN if it read
+    // from the terminal, as the runs of watch mode are not in its foreground process group.
+    let mom = assert_cmd::cargo::cargo_bin("mom");
+    let mut child = std::process::Command::new("script")
+        .current_dir(tmp_dir.path())
+        .arg("-qfec")
+        .arg(format!("{} --watch ask", mom.display()))
+        .arg("/dev/null")
+        .stdin(std::process::Stdio::piped())
+        .stdout(File::create(&output_path)?)
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+    child.kill()?;
+    child.wait()?;
+
+    let output = std::fs::read_to_string(&output_path)?;
+    assert!(output.contains("ask.cmds.0: head -n 1"));
+    assert!(output.contains("ask.cmds.1: echo done"));
+    assert!(output.contains("Waiting for changes..."));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_finally_on_interrupt() {
+    use std::os::unix::process::CommandExt;
+
+    let tmp_dir = TempDir::new().unwrap();
+
+    let mut file = File::create(tmp_dir.join("mom.root.yml")).unwrap();
+    file.write_all(
+        r#"
+version: 1
+
+tasks:
+    test:
+        cmds:
+            - sleep 5
+            - echo "never"
+        finally:
+            - echo "cleanup"
+"#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    // In its own process group, to send Ctrl-C to it and its children like a terminal would
+    let child = std::process::Command::new(assert_cmd::cargo::cargo_bin("mom"))
+        .current_dir(tmp_dir.path())
+        .arg("test")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .process_group(0)
+        .spawn()
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    unsafe {
+        libc::kill(-(child.id() as i32), libc::SIGINT);
+    }
+    let output = child.wait_with_output().unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+
+    assert!(!output.contains("\nnever\n"));
+    assert!(output.contains("test.finally.0: echo cleanup\ncleanup\n"));
 }